@@ -3,7 +3,8 @@
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use lo_shu::{
-    CheckScalar, CheckVector, Construction, Enumerable, ParameterSetError, Permutation, O3, O4, O5,
+    CheckScalar, CheckVector, Construction, Enumerable, MagicChecker, ParameterSetError,
+    Permutation, O10, O3, O4, O5,
 };
 use std::time::Duration;
 
@@ -33,13 +34,13 @@ fn check_safe_scalar() {
 
 fn check_generic_three_v() {
     let a = Construction::<O3>::siamese(1);
-    let r = a.check_n_v::<8>();
+    let r = a.check_n_v();
     assert_eq!(Some(a), r);
 }
 
 fn check_generic_four_v() -> Result<(), ParameterSetError> {
     let c = Construction::try_from(Permutation::<O4>::kth(80867885530))?;
-    let r = c.check_n_v::<16>();
+    let r = c.check_n_v();
     assert_eq!(Some(c), r);
 
     Ok(())
@@ -47,7 +48,7 @@ fn check_generic_four_v() -> Result<(), ParameterSetError> {
 
 fn check_generic_five_v() {
     let a = Construction::<O5>::siamese(2);
-    let r = a.check_n_v::<16>();
+    let r = a.check_n_v();
     assert_eq!(Some(a), r);
 }
 
@@ -71,6 +72,30 @@ fn check_generic_five_s() {
     assert_eq!(Some(a), r);
 }
 
+fn check_dispatch_three() {
+    let a = Permutation::<O3>::kth(69074);
+    let r = a.check();
+    assert_eq!(Some(a), r);
+}
+
+fn check_dispatch_four() {
+    let a = Permutation::<O4>::kth(80867885530);
+    let r = a.check();
+    assert_eq!(Some(a), r);
+}
+
+fn check_dispatch_five() {
+    let a = Construction::<O5>::siamese(2);
+    let r = a.check();
+    assert_eq!(Some(a), r);
+}
+
+fn check_dispatch_ten() {
+    let a = Construction::<O10>::identity();
+    let r = a.check();
+    assert_eq!(None, r);
+}
+
 pub fn check_v_bench(c: &mut Criterion) {
     let mut group = c.benchmark_group("check_v");
     group.sample_size(10500);
@@ -133,6 +158,30 @@ pub fn check_generic_bench_s(c: &mut Criterion) {
     group.finish();
 }
 
+pub fn check_dispatch_bench(c: &mut Criterion) {
+    let mut group = c.benchmark_group("check_dispatch");
+    group.sample_size(10500);
+    group.noise_threshold(0.03);
+    group.measurement_time(Duration::new(20, 0));
+    group.bench_function(
+        format!("order_three ({})", Permutation::<O3>::backend()),
+        |b| b.iter(black_box(check_dispatch_three)),
+    );
+    group.bench_function(
+        format!("order_four ({})", Permutation::<O4>::backend()),
+        |b| b.iter(black_box(check_dispatch_four)),
+    );
+    group.bench_function(
+        format!("order_five ({})", Construction::<O5>::backend()),
+        |b| b.iter(black_box(check_dispatch_five)),
+    );
+    group.bench_function(
+        format!("order_ten ({})", Construction::<O10>::backend()),
+        |b| b.iter(black_box(check_dispatch_ten)),
+    );
+    group.finish();
+}
+
 criterion_group!(
     benches,
     check_v_bench,
@@ -140,6 +189,7 @@ criterion_group!(
     check_safe_s_v_bench,
     check_unsafe_s_v_bench,
     check_generic_bench_v,
-    check_generic_bench_s
+    check_generic_bench_s,
+    check_dispatch_bench
 );
 criterion_main!(benches);
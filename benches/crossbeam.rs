@@ -2,7 +2,7 @@ use std::thread;
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use crossbeam_channel::unbounded;
-use lo_shu::{Enumerable, Permutation, O3};
+use lo_shu::{PermutationRange, O3};
 
 fn crossbeam_solver_o3(t: usize) {
     let (s, r) = unbounded();
@@ -10,8 +10,8 @@ fn crossbeam_solver_o3(t: usize) {
     for i in 0..t {
         let sender = s.clone();
         thread::spawn(move || {
-            for n in (i..69075).step_by(t) {
-                if let Some(sol) = Permutation::<O3>::kth(n.try_into().unwrap()).check_n_s() {
+            for p in PermutationRange::<O3>::new(i as u32..69075).step_by(t) {
+                if let Some(sol) = p.check_n_s() {
                     match sender.send(sol) {
                         Ok(_) => {}
                         Err(_) => {}
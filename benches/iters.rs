@@ -4,23 +4,22 @@
 #![feature(iter_array_chunks)]
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use lo_shu::{CheckVector, Enumerable, Permutation, O3};
+use lo_shu::{CheckVector, IndexConst, PermutationRange, O3};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::{collections::HashSet, time::Duration};
 
 fn iter_solve_3() {
-    let b: HashSet<_> = (0..362880)
-        .into_iter()
-        .filter_map(|k| Permutation::<O3>::kth(k).check_v())
+    let b: HashSet<_> = PermutationRange::<O3>::new(0..O3::MAX_INDEX)
+        .filter_map(|p| p.check_v())
         .collect();
 
     assert!(b.len() == 8)
 }
 
 fn par_iter_solve_3() {
-    let b: HashSet<_> = (0..362880)
+    let b: HashSet<_> = PermutationRange::<O3>::new(0..O3::MAX_INDEX)
         .into_par_iter()
-        .filter_map(|k| Permutation::<O3>::kth(k).check_v())
+        .filter_map(|p| p.check_v())
         .collect();
 
     assert!(b.len() == 8)
@@ -28,8 +27,8 @@ fn par_iter_solve_3() {
 
 fn iter_dihedral_solve_3() {
     let mut result = HashSet::new();
-    for k in 0..362880 {
-        if let Some(p) = Permutation::<O3>::kth(k).check_v() {
+    for p in PermutationRange::<O3>::new(0..O3::MAX_INDEX) {
+        if let Some(p) = p.check_v() {
             result.clone_from(&p.generate_d());
             break;
         }
@@ -39,9 +38,9 @@ fn iter_dihedral_solve_3() {
 }
 
 fn par_iter_dihedral_solve_3() {
-    let a = (0..362880)
+    let a = PermutationRange::<O3>::new(0..O3::MAX_INDEX)
         .into_par_iter()
-        .find_map_first(|i| Permutation::<O3>::kth(i).check_v())
+        .find_map_first(|p| p.check_v())
         .unwrap()
         .generate_d();
 
@@ -7,7 +7,7 @@ use std::{
 };
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use lo_shu::{Enumerable, IndexConst, Permutation, O3};
+use lo_shu::{IndexConst, Permutation, PermutationRange, O3};
 
 pub fn from_builder(t: usize) -> Result<(), anyhow::Error> {
     let (sx, rx) = mpsc::channel();
@@ -15,8 +15,8 @@ pub fn from_builder(t: usize) -> Result<(), anyhow::Error> {
     for i in 0..t {
         let sender: Sender<Permutation<O3>> = sx.clone();
         thread::spawn(move || {
-            for n in (i as u32..O3::MAX_INDEX).step_by(t) {
-                if let Some(sol) = Permutation::<O3>::kth(n).check_n_s() {
+            for p in PermutationRange::<O3>::new(i as u32..O3::MAX_INDEX).step_by(t) {
+                if let Some(sol) = p.check_n_s() {
                     match sender.send(sol) {
                         Ok(_) => {}
                         Err(_) => {}
@@ -7,7 +7,7 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-use lo_shu::{CheckVector, Enumerable, Permutation, O3};
+use lo_shu::{CheckVector, Enumerable, IndexConst, Permutation, PermutationRange, O3};
 
 #[inline]
 fn solve_t_v1() -> BTreeSet<u32> {
@@ -58,9 +58,9 @@ fn solve_t_v2() -> BTreeSet<u32> {
     for t in 1..=THREADS {
         let r = results.clone();
         let handle = thread::spawn(move || {
-            let local_res = (t..362880)
+            let local_res = PermutationRange::<O3>::new(t as u32..O3::MAX_INDEX)
                 .step_by(t)
-                .filter_map(|a| Permutation::<O3>::kth(a.try_into().unwrap()).check_v())
+                .filter_map(|p| p.check_v())
                 .map(|b| b.clone().index());
             let mut global_res = r.lock().unwrap();
             for i in local_res {
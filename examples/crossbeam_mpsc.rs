@@ -1,7 +1,7 @@
 use std::thread;
 
 use crossbeam_channel::{self, unbounded};
-use lo_shu::{Enumerable, Permutation, O3};
+use lo_shu::{PermutationRange, O3};
 
 fn main() {
     let (s, r) = unbounded();
@@ -9,8 +9,8 @@ fn main() {
     for i in 0..16 {
         let sender = s.clone();
         thread::spawn(move || {
-            for n in (i..69074).step_by(16) {
-                if let Some(sol) = Permutation::<O3>::kth(n.try_into().unwrap()).check_n_s() {
+            for p in PermutationRange::<O3>::new(i..69074).step_by(16) {
+                if let Some(sol) = p.check_n_s() {
                     match sender.send(sol) {
                         Ok(_) => {}
                         Err(_) => {}
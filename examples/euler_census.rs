@@ -0,0 +1,26 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use std::collections::BTreeSet;
+
+use lo_shu::{read_serial, Enumerable, Permutation, O4};
+
+// Counts the squares in the order four census whose Euler components are both Latin squares.
+fn main() {
+    let census: BTreeSet<u64> = read_serial("examples/collected/orderfour/Census.txt").unwrap();
+
+    let euler_type = census
+        .iter()
+        .map(|&i| Permutation::<O4>::kth(i))
+        .filter(|p| p.euler_components().is_euler_type())
+        .collect::<BTreeSet<_>>();
+
+    let unique = euler_type
+        .iter()
+        .filter(|p| p.generate_d().into_iter().min().as_ref() == Some(*p))
+        .count();
+
+    println!("Census: {}", census.len());
+    println!("Euler-type: {}", euler_type.len());
+    println!("Euler-type (unique up to symmetry): {unique}");
+}
@@ -3,9 +3,8 @@
 
 use lo_shu::prelude::*;
 use lo_shu::read_serial;
-use lo_shu::{CheckVector, Enumerable, Permutation, O4};
+use lo_shu::{Enumerable, Permutation, O4};
 
-use itertools::Itertools;
 use std::collections::BTreeSet;
 use std::time::Instant;
 
@@ -31,18 +30,7 @@ fn main() {
 
     println!("Extending...");
 
-    let mut ext = BTreeSet::new();
-    unique_set
-        .iter()
-        .cartesian_product(actions.iter())
-        .for_each(|(&a, &b)| {
-            for iso_e in b.generate_d() {
-                if let Some(m) = (a * iso_e.inv()).check_v() {
-                    ext.insert(m);
-                }
-            }
-        });
-
+    let ext = extend_by_actions(&unique_set, &actions);
     unique_set.extend(&ext);
 
     let unique_set = unique_squares(&unique_set);
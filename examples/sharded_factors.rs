@@ -0,0 +1,33 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use lo_shu::prelude::*;
+use lo_shu::read_serial;
+use lo_shu::{Enumerable, Permutation, O4};
+
+use std::collections::BTreeSet;
+
+/// Computes the factors of every pair of squares of the order four census, 7040 squares and
+/// 49.5 M products. Pass a directory to spill the shards to it, and optionally the number of
+/// factors a shard holds before spilling.
+fn main() {
+    let file: BTreeSet<u64> = read_serial("examples/collected/orderfour/Census.txt").unwrap();
+    let census = file
+        .into_iter()
+        .map(Permutation::<O4>::kth)
+        .collect::<BTreeSet<_>>();
+
+    println!("Input Size: {}", census.len());
+
+    let mut args = std::env::args().skip(1);
+    let mut pipeline = ShardedFactors::new();
+    if let Some(dir) = args.next() {
+        let limit = args.next().map_or(1 << 16, |l| l.parse().unwrap());
+        pipeline = pipeline.spill_to(dir, limit);
+    }
+
+    let (factors, stats) = pipeline.run(&census).unwrap();
+
+    println!("Factors: {}", factors.len());
+    println!("{stats}");
+}
@@ -4,20 +4,20 @@
 use lo_shu::CheckVector;
 use std::collections::HashSet;
 
-use lo_shu::{Enumerable, Permutation, O3};
+use lo_shu::{IndexConst, Permutation, PermutationRange, O3};
 use rayon::prelude::*;
 
 fn order_three_linear() -> HashSet<Permutation<O3>> {
-    (0..362880)
+    PermutationRange::<O3>::new(0..O3::MAX_INDEX)
         .into_par_iter()
-        .filter_map(|k| Permutation::<O3>::kth(k).check_v())
+        .filter_map(|p| p.check_v())
         .collect()
 }
 
 fn order_three_dihedral() -> HashSet<Permutation<O3>> {
-    (0..362880)
+    PermutationRange::<O3>::new(0..O3::MAX_INDEX)
         .into_par_iter()
-        .find_map_first(|i| Permutation::<O3>::kth(i).check_v())
+        .find_map_first(|p| p.check_v())
         .unwrap()
         .generate_d()
 }
@@ -0,0 +1,32 @@
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use lo_shu::{read_serial, Enumerable, Permutation, SquareMatrix, O4};
+
+fn main() {
+    let magic_squares: BTreeSet<u64> =
+        read_serial("examples/collected/orderfour/UniqueCensus.txt").unwrap();
+
+    let mut spectrum_map: BTreeMap<Vec<i128>, Vec<Permutation<O4>>> = BTreeMap::new();
+
+    for i in magic_squares {
+        let square = Permutation::<O4>::kth(i);
+        let polynomial = SquareMatrix::from(&square.square).characteristic_polynomial();
+        spectrum_map.entry(polynomial).or_default().push(square);
+    }
+
+    let mut sum = 0;
+    for (polynomial, squares) in &spectrum_map {
+        let matrix = SquareMatrix::from(&squares[0].square);
+        sum += squares.len();
+        println!(
+            "{:?} rank {} : {}",
+            polynomial,
+            matrix.rank(),
+            squares.len()
+        );
+    }
+    println!("{} spectra, {} squares", spectrum_map.len(), sum)
+}
@@ -0,0 +1,92 @@
+use crate::{constraint_lines, gcd, Params};
+
+use super::matrix::row_reduce;
+
+/// Returns a basis of the vector space of `P::ORDER` × `P::ORDER` magic matrices, whose rows,
+/// columns and both diagonals share a sum, which may be any number.
+///
+/// The space is the null space of the differences between the sum of every line and that of
+/// the first row, found by exact row reduction. Each basis matrix has integer entries with no
+/// common factor and is given row by row. The dimension is `n² - 2n` from order 3 on.
+#[must_use]
+pub fn magic_basis<P: Params>() -> Vec<Vec<i64>> {
+    let lines = constraint_lines::<P>();
+    let indicator = |line: &[usize]| {
+        let mut row = vec![0i128; P::ELEMENTS];
+        for &c in line {
+            row[c] = 1;
+        }
+        row
+    };
+
+    let first = indicator(&lines[0]);
+    let mut rows = lines[1..]
+        .iter()
+        .map(|line| {
+            let row = indicator(line);
+            row.iter().zip(&first).map(|(a, b)| a - b).collect()
+        })
+        .collect::<Vec<Vec<i128>>>();
+    let pivots = row_reduce(&mut rows);
+    let scale = rows
+        .iter()
+        .zip(&pivots)
+        .fold(1, |l, (row, &p)| lcm(l, row[p]));
+
+    (0..P::ELEMENTS)
+        .filter(|c| !pivots.contains(c))
+        .map(|free| {
+            let mut v = vec![0i128; P::ELEMENTS];
+            v[free] = scale;
+            for (row, &p) in rows.iter().zip(&pivots) {
+                v[p] = -row[free] * scale / row[p];
+            }
+
+            let g = v.iter().fold(0, |g, &x| gcd(g, x));
+            v.into_iter().map(|x| (x / g) as i64).collect()
+        })
+        .collect()
+}
+
+fn lcm(a: i128, b: i128) -> i128 {
+    a / gcd(a, b) * b.abs()
+}
+
+#[cfg(test)]
+mod test_basis {
+    use crate::{O3, O4, O5};
+
+    use super::*;
+
+    fn is_magic(v: &[i64], n: usize) -> bool {
+        let lines = (0..n)
+            .map(|i| (0..n).map(|j| v[i * n + j]).sum())
+            .chain((0..n).map(|j| (0..n).map(|i| v[i * n + j]).sum()))
+            .chain([
+                (0..n).map(|i| v[i * (n + 1)]).sum(),
+                (0..n).map(|i| v[(i + 1) * (n - 1)]).sum(),
+            ])
+            .collect::<Vec<i64>>();
+        lines.iter().all(|&s| s == lines[0])
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert_eq!(magic_basis::<O3>().len(), 3);
+        assert_eq!(magic_basis::<O4>().len(), 8);
+        assert_eq!(magic_basis::<O5>().len(), 15);
+    }
+
+    #[test]
+    fn test_basis() {
+        let basis = magic_basis::<O4>();
+        assert!(basis.iter().all(|v| is_magic(v, 4)));
+        assert!(basis.iter().all(|v| v.iter().any(|&x| x != 0)));
+
+        let mut rows = basis
+            .iter()
+            .map(|v| v.iter().map(|&x| i128::from(x)).collect())
+            .collect::<Vec<Vec<i128>>>();
+        assert_eq!(row_reduce(&mut rows).len(), 8);
+    }
+}
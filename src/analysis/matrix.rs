@@ -0,0 +1,447 @@
+use crate::{gcd, Construction, Params, Square};
+
+/// A square of integers viewed as a matrix, for linear algebra on magic squares.
+///
+/// The rank, determinant and characteristic polynomial are exact, computed over the integers.
+/// The eigenvalues and singular values are floating point. Every square whose rows share a sum
+/// has that sum as an eigenvalue, with the all-ones eigenvector, so it is split off exactly.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{Construction, SquareMatrix, O3};
+///
+/// let a = SquareMatrix::from(&Construction::<O3>::siamese(1));
+///
+/// assert_eq!(a.rank(), 3);
+/// assert_eq!(a.characteristic_polynomial(), vec![1, -15, -24, 360]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SquareMatrix {
+    order: usize,
+    data: Vec<i64>,
+}
+
+impl SquareMatrix {
+    /// Creates a matrix of order `order` from its entries, row by row.
+    ///
+    /// # Panics
+    /// - If the number of entries is not `order * order`.
+    #[must_use]
+    pub fn new(order: usize, data: Vec<i64>) -> Self {
+        assert_eq!(
+            data.len(),
+            order * order,
+            "Item length does not match order²!"
+        );
+        Self { order, data }
+    }
+
+    /// Returns the order.
+    #[must_use]
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// Returns the entries, row by row.
+    #[must_use]
+    pub fn data(&self) -> &[i64] {
+        &self.data
+    }
+
+    /// Returns the rank, by fraction-free row reduction.
+    #[must_use]
+    pub fn rank(&self) -> usize {
+        let mut rows = self.rows();
+        row_reduce(&mut rows).len()
+    }
+
+    /// Returns the determinant, by Bareiss elimination, which keeps every entry an integer.
+    ///
+    /// # Panics
+    /// - If an intermediate value overflows `i128`.
+    #[must_use]
+    pub fn determinant(&self) -> i128 {
+        let n = self.order;
+        let mut a = self.rows();
+        let (mut sign, mut previous) = (1, 1);
+
+        for k in 0..n {
+            let Some(pivot) = (k..n).find(|&i| a[i][k] != 0) else {
+                return 0;
+            };
+            if pivot != k {
+                a.swap(pivot, k);
+                sign = -sign;
+            }
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let cross = checked(
+                        checked(a[k][k].checked_mul(a[i][j]))
+                            .checked_sub(checked(a[i][k].checked_mul(a[k][j]))),
+                    );
+                    a[i][j] = cross / previous;
+                }
+            }
+            previous = a[k][k];
+        }
+
+        if n == 0 {
+            1
+        } else {
+            sign * a[n - 1][n - 1]
+        }
+    }
+
+    /// Returns the characteristic polynomial `det(xI - A)`, by the Faddeev–LeVerrier algorithm,
+    /// as its integer coefficients from `x^n` down to the constant term.
+    ///
+    /// # Panics
+    /// - If an intermediate value overflows `i128`.
+    #[must_use]
+    pub fn characteristic_polynomial(&self) -> Vec<i128> {
+        let n = self.order;
+        let a = self.rows();
+        let mut coefficients = vec![1];
+        let mut m = vec![vec![0i128; n]; n];
+
+        for k in 1..=n {
+            let c = coefficients[k - 1];
+            for (i, row) in m.iter_mut().enumerate() {
+                row[i] += c;
+            }
+            m = multiply(&a, &m);
+            let trace = (0..n).map(|i| m[i][i]).sum::<i128>();
+            coefficients.push(-trace / k as i128);
+        }
+
+        coefficients
+    }
+
+    /// Returns the eigenvalues as `(re, im)` pairs, with multiplicity, largest modulus first.
+    ///
+    /// Zero roots of the characteristic polynomial and the common row sum, if the rows share
+    /// one, are split off exactly. The remaining roots are found by the Durand–Kerner method,
+    /// so repeated roots are only accurate to about `1e-8`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn eigenvalues(&self) -> Vec<(f64, f64)> {
+        let mut p = self.characteristic_polynomial();
+        let mut values = vec![];
+
+        while p.len() > 1 && p[p.len() - 1] == 0 {
+            p.pop();
+            values.push((0.0, 0.0));
+        }
+        if let Some(sum) = self.row_sum() {
+            if sum != 0 {
+                if let Some(q) = divide_root(&p, i128::from(sum)) {
+                    p = q;
+                    values.push((sum as f64, 0.0));
+                }
+            }
+        }
+
+        values.extend(roots(&p));
+        values.sort_by(|a, b| {
+            let modulus = |(re, im): &(f64, f64)| re.hypot(*im);
+            modulus(b)
+                .total_cmp(&modulus(a))
+                .then(b.0.total_cmp(&a.0))
+                .then(b.1.total_cmp(&a.1))
+        });
+
+        values
+    }
+
+    /// Returns the singular values, largest first, as the square roots of the eigenvalues of
+    /// `AᵀA` found by the cyclic Jacobi method. The largest singular value of a magic square is
+    /// its magic sum.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn singular_values(&self) -> Vec<f64> {
+        let n = self.order;
+        let a = |i: usize, j: usize| self.data[i * n + j] as f64;
+        let mut b = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| (0..n).map(|k| a(k, i) * a(k, j)).sum())
+                    .collect()
+            })
+            .collect::<Vec<Vec<f64>>>();
+
+        jacobi(&mut b);
+        let mut values = (0..n).map(|i| b[i][i].max(0.0).sqrt()).collect::<Vec<_>>();
+        values.sort_by(|x, y| y.total_cmp(x));
+
+        values
+    }
+
+    /// Returns the sum every row shares, if there is one.
+    #[must_use]
+    pub fn row_sum(&self) -> Option<i64> {
+        let mut sums = self.data.chunks(self.order).map(|row| row.iter().sum());
+        let first = sums.next()?;
+
+        sums.all(|s: i64| s == first).then_some(first)
+    }
+
+    fn rows(&self) -> Vec<Vec<i128>> {
+        self.data
+            .chunks(self.order)
+            .map(|row| row.iter().map(|&x| i128::from(x)).collect())
+            .collect()
+    }
+}
+
+impl<P: Params> From<&Square<P>> for SquareMatrix
+where
+    [(); P::ELEMENTS]:,
+{
+    fn from(item: &Square<P>) -> Self {
+        Self::new(P::ORDER, item.data.iter().map(|&x| i64::from(x)).collect())
+    }
+}
+
+impl<P: Params> From<&Construction<P>> for SquareMatrix
+where
+    [(); P::ELEMENTS]:,
+{
+    fn from(item: &Construction<P>) -> Self {
+        Self::new(
+            P::ORDER,
+            item.square.data.iter().map(|&x| i64::from(x)).collect(),
+        )
+    }
+}
+
+fn checked(value: Option<i128>) -> i128 {
+    value.expect("Matrix entries overflowed i128!")
+}
+
+fn multiply(a: &[Vec<i128>], b: &[Vec<i128>]) -> Vec<Vec<i128>> {
+    let n = a.len();
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    (0..n).fold(0i128, |acc, k| {
+                        checked(acc.checked_add(checked(a[i][k].checked_mul(b[k][j]))))
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Brings integer rows to reduced row echelon form without fractions, each row divided by the
+/// gcd of its entries, and returns the pivot column of every nonzero row, which come first.
+pub(crate) fn row_reduce(rows: &mut Vec<Vec<i128>>) -> Vec<usize> {
+    let width = rows.first().map_or(0, Vec::len);
+    let mut pivots = vec![];
+
+    for col in 0..width {
+        let r = pivots.len();
+        let Some(pivot) = (r..rows.len()).find(|&i| rows[i][col] != 0) else {
+            continue;
+        };
+        rows.swap(pivot, r);
+
+        let others = (0..rows.len()).filter(|&i| i != r && rows[i][col] != 0);
+        let pivot_row = rows[r].clone();
+        for i in others.collect::<Vec<_>>() {
+            let (p, q) = (pivot_row[col], rows[i][col]);
+            for (x, &y) in rows[i].iter_mut().zip(&pivot_row) {
+                *x = checked(p.checked_mul(*x)) - checked(q.checked_mul(y));
+            }
+            let g = rows[i].iter().fold(0, |g, &x| gcd(g, x));
+            if g > 1 {
+                rows[i].iter_mut().for_each(|x| *x /= g);
+            }
+        }
+        pivots.push(col);
+    }
+    rows.truncate(pivots.len());
+
+    pivots
+}
+
+/// Divides a polynomial, coefficients from the highest power, by `x - root` if it is a root.
+fn divide_root(p: &[i128], root: i128) -> Option<Vec<i128>> {
+    let mut quotient = Vec::with_capacity(p.len() - 1);
+    let mut carry = 0i128;
+    for &c in p {
+        carry = checked(carry.checked_mul(root)) + c;
+        quotient.push(carry);
+    }
+
+    (quotient.pop() == Some(0)).then_some(quotient)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn roots(p: &[i128]) -> Vec<(f64, f64)> {
+    let degree = p.len() - 1;
+    if degree == 0 {
+        return vec![];
+    }
+
+    let lead = p[0] as f64;
+    let c = p.iter().map(|&x| x as f64 / lead).collect::<Vec<_>>();
+    let eval = |z: (f64, f64)| {
+        c.iter().fold((0.0, 0.0), |acc, &k| {
+            let (re, im) = mul(acc, z);
+            (re + k, im)
+        })
+    };
+    let bound = 1.0 + c[1..].iter().fold(0.0f64, |m, x| m.max(x.abs()));
+
+    let mut z = (0..degree)
+        .map(|k| {
+            let angle = 0.4 + std::f64::consts::TAU * k as f64 / degree as f64;
+            (bound * angle.cos(), bound * angle.sin())
+        })
+        .collect::<Vec<_>>();
+    for _ in 0..1000 {
+        let mut moved = 0.0f64;
+        for i in 0..degree {
+            let denominator = (0..degree).filter(|&j| j != i).fold((1.0, 0.0), |acc, j| {
+                mul(acc, (z[i].0 - z[j].0, z[i].1 - z[j].1))
+            });
+            let step = div(eval(z[i]), denominator);
+            z[i] = (z[i].0 - step.0, z[i].1 - step.1);
+            moved = moved.max(step.0.hypot(step.1));
+        }
+        if moved < 1e-14 * bound {
+            break;
+        }
+    }
+
+    z
+}
+
+fn mul(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn div(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let d = b.0 * b.0 + b.1 * b.1;
+    ((a.0 * b.0 + a.1 * b.1) / d, (a.1 * b.0 - a.0 * b.1) / d)
+}
+
+/// Diagonalises a symmetric matrix in place by cyclic Jacobi rotations.
+#[allow(clippy::needless_range_loop)]
+fn jacobi(b: &mut [Vec<f64>]) {
+    let n = b.len();
+    let scale = b.iter().flatten().map(|x| x * x).sum::<f64>().sqrt();
+
+    for _ in 0..100 {
+        let off = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| b[i][j] * b[i][j])
+            .sum::<f64>()
+            .sqrt();
+        if off <= 1e-15 * scale {
+            return;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if b[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (b[q][q] - b[p][p]) / (2.0 * b[p][q]);
+                let t = theta.signum() / (theta.abs() + theta.hypot(1.0));
+                let t = if theta == 0.0 { 1.0 } else { t };
+                let (c, s) = (1.0 / t.hypot(1.0), t / t.hypot(1.0));
+
+                for row in b.iter_mut() {
+                    let (x, y) = (row[p], row[q]);
+                    row[p] = c * x - s * y;
+                    row[q] = s * x + c * y;
+                }
+                for k in 0..n {
+                    let (x, y) = (b[p][k], b[q][k]);
+                    b[p][k] = c * x - s * y;
+                    b[q][k] = s * x + c * y;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_matrix {
+    use crate::{Permutation, O3, O4};
+
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_lo_shu() {
+        let a = SquareMatrix::from(&Construction::<O3>::siamese(1));
+
+        assert_eq!(a.rank(), 3);
+        assert_eq!(a.determinant(), -360);
+        assert_eq!(a.characteristic_polynomial(), vec![1, -15, -24, 360]);
+
+        let e = a.eigenvalues();
+        let root = 24f64.sqrt();
+        assert!(close(e[0].0, 15.0) && close(e[1].0, root) && close(e[2].0, -root));
+        assert!(e.iter().all(|&(_, im)| close(im, 0.0)));
+
+        let s = a.singular_values();
+        assert!(close(s[0], 15.0));
+        assert!(close(s.iter().product::<f64>(), 360.0));
+    }
+
+    #[test]
+    fn test_durer() {
+        let a = SquareMatrix::new(
+            4,
+            vec![16, 3, 2, 13, 5, 10, 11, 8, 9, 6, 7, 12, 4, 15, 14, 1],
+        );
+
+        assert_eq!(a.rank(), 3);
+        assert_eq!(a.determinant(), 0);
+        assert_eq!(a.characteristic_polynomial(), vec![1, -34, -64, 2176, 0]);
+
+        let e = a.eigenvalues();
+        for (value, expected) in e.iter().zip([34.0, 8.0, -8.0, 0.0]) {
+            assert!(close(value.0, expected) && close(value.1, 0.0));
+        }
+
+        let s = a.singular_values();
+        let root = 5f64.sqrt();
+        for (value, expected) in s.iter().zip([34.0, 8.0 * root, 2.0 * root, 0.0]) {
+            assert!(close(*value, expected));
+        }
+    }
+
+    #[test]
+    fn test_square() {
+        let a = SquareMatrix::from(&Permutation::<O4>::identity().square);
+
+        assert_eq!(a.rank(), 2);
+        assert_eq!(a.determinant(), 0);
+        assert_eq!(a.row_sum(), None);
+        assert_eq!(a.eigenvalues().len(), 4);
+        assert_eq!(
+            SquareMatrix::new(2, vec![0, 1, -1, 0]).eigenvalues().len(),
+            2
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "overflowed i128")]
+    fn test_determinant_overflow() {
+        // Every product fits in i128, but the second elimination step subtracts two of them
+        // past i128::MIN.
+        let min = i64::MIN;
+        let a = SquareMatrix::new(3, vec![1, 0, min, 0, 1, min, min, min, min]);
+
+        let _ = a.determinant();
+    }
+}
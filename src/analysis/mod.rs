@@ -0,0 +1,5 @@
+mod basis;
+mod matrix;
+
+pub use basis::*;
+pub use matrix::*;
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::{
+    check_n_s_data, sum_constraint_vectors_v, vector_lanes, CheckScalar, CheckVector,
+    CompactPermutation, Construction, PackedPerm16, Params, Permutation, Square, VecSquare, O3, O4,
+    O5,
+};
+
+/// An implementation of the magic check, as chosen by a `MagicChecker`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckBackend {
+    /// The scalar check unrolled for orders 3 and 4, `check_s_unsafe`.
+    FixedScalar,
+    /// The scalar check of any order, `check_n_s`.
+    Scalar,
+    /// The SIMD check built from swizzles for orders 3 to 5, `check_v_unsafe`.
+    FixedVector,
+    /// The SIMD check of any order, `check_n_v`, adding up `lanes` cells of a line at a time.
+    Vector { lanes: usize },
+}
+
+impl fmt::Display for CheckBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckBackend::FixedScalar => write!(f, "fixed scalar"),
+            CheckBackend::Scalar => write!(f, "scalar"),
+            CheckBackend::FixedVector => write!(f, "fixed vector"),
+            CheckBackend::Vector { lanes } => write!(f, "vector ({lanes} lanes)"),
+        }
+    }
+}
+
+/// Returns the width in bits of the widest SIMD registers of the CPU, or 0 if it has none. The
+/// CPU is only queried the first time.
+#[must_use]
+pub fn simd_width() -> usize {
+    static WIDTH: OnceLock<usize> = OnceLock::new();
+
+    *WIDTH.get_or_init(|| {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx512f") {
+                512
+            } else if is_x86_feature_detected!("avx2") {
+                256
+            } else if is_x86_feature_detected!("sse2") {
+                128
+            } else {
+                0
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            if std::arch::is_aarch64_feature_detected!("neon") {
+                128
+            } else {
+                0
+            }
+        }
+        #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+        {
+            0
+        }
+    })
+}
+
+/// Check if a square-like object is magic, with the fastest implementation available for its
+/// order on this CPU.
+///
+/// Orders 3 and 4 use the unrolled scalar checks, and larger orders `check_n_v` in lanes chosen
+/// from the SIMD width of the CPU, or `check_n_s` when it has no SIMD registers. The length of
+/// the entries is checked once up front, so the fixed checks skip their bounds checks.
+pub trait MagicChecker: Sized {
+    /// The implementation `check` uses for this order on this CPU.
+    fn backend() -> CheckBackend;
+
+    /// Check if a square-like object is magic, using the implementation given by `backend`.
+    fn check(&self) -> Option<Self>;
+}
+
+/// Chooses the implementation of the magic check for an order on this CPU.
+///
+/// The unrolled scalar checks of orders 3 and 4 were measured to beat every SIMD check, which
+/// spend longer loading vectors than adding. From order 5, `check_n_v` beats both `check_n_s`,
+/// tenfold, and the SIMD check built for order 5. It adds in as many lanes as the widest SIMD
+/// register holds, up to the order rounded up to a power of two.
+fn select<P: Params>() -> CheckBackend {
+    match P::ORDER {
+        3 | 4 => CheckBackend::FixedScalar,
+        n if simd_width() > 0 => CheckBackend::Vector {
+            lanes: vector_lanes(n),
+        },
+        _ => CheckBackend::Scalar,
+    }
+}
+
+/// Copies the entries of a square into a fixed square, without building the error message of
+/// `Square::try_from`.
+#[inline]
+fn fixed<Q: Params>(data: &[u32]) -> Option<Square<Q>>
+where
+    [(); Q::ELEMENTS]:,
+{
+    <[u32; Q::ELEMENTS]>::try_from(data)
+        .ok()
+        .map(Square::from_array)
+}
+
+#[inline]
+fn check_data<P: Params>(data: &[u32], backend: CheckBackend) -> bool
+where
+    [(); P::ORDER]:,
+{
+    if data.len() != P::ELEMENTS {
+        return false;
+    }
+
+    // Each fixed square holds exactly the entries its unchecked loads read.
+    match (backend, P::ORDER) {
+        (CheckBackend::FixedScalar, 3) => {
+            fixed::<O3>(data).is_some_and(|s| unsafe { s.check_s_unsafe() }.is_some())
+        }
+        (CheckBackend::FixedScalar, 4) => {
+            fixed::<O4>(data).is_some_and(|s| unsafe { s.check_s_unsafe() }.is_some())
+        }
+        (CheckBackend::FixedVector, 3) => {
+            fixed::<O3>(data).is_some_and(|s| unsafe { s.check_v_unsafe() }.is_some())
+        }
+        (CheckBackend::FixedVector, 4) => {
+            fixed::<O4>(data).is_some_and(|s| unsafe { s.check_v_unsafe() }.is_some())
+        }
+        (CheckBackend::FixedVector, 5) => {
+            fixed::<O5>(data).is_some_and(|s| unsafe { s.check_v_unsafe() }.is_some())
+        }
+        (CheckBackend::Vector { lanes }, _) => sum_constraint_vectors_v::<P>(data, lanes),
+        _ => check_n_s_data::<P>(data).is_some(),
+    }
+}
+
+// Reduce code duplication
+//-------------------------------------------------------------------------------------------------
+
+macro_rules! impl_magic_checker_for_type {
+    ($t:tt, $($data:ident).+) => {
+        impl<P: Params + Copy> MagicChecker for $t<P>
+        where
+            [(); P::ELEMENTS]:,
+            [(); P::ORDER]:,
+        {
+            fn backend() -> CheckBackend {
+                select::<P>()
+            }
+
+            #[inline]
+            fn check(&self) -> Option<Self> {
+                check_data::<P>(&self.$($data).+, Self::backend()).then(|| self.clone())
+            }
+        }
+    };
+}
+
+impl_magic_checker_for_type!(Square, data);
+impl_magic_checker_for_type!(VecSquare, data);
+impl_magic_checker_for_type!(Permutation, square.data);
+impl_magic_checker_for_type!(Construction, square.data);
+
+/// Compact permutations are checked on their cells by `check_n_s`, since every faster check
+/// would first widen them to `u32`.
+impl<P: Params + Copy> MagicChecker for CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn backend() -> CheckBackend {
+        CheckBackend::Scalar
+    }
+
+    #[inline]
+    fn check(&self) -> Option<Self> {
+        self.check_n_s()
+    }
+}
+
+/// Packed permutations have a single check, on the bytes of a vector.
+impl MagicChecker for PackedPerm16 {
+    fn backend() -> CheckBackend {
+        CheckBackend::FixedVector
+    }
+
+    #[inline]
+    fn check(&self) -> Option<Self> {
+        self.check_v()
+    }
+}
+
+#[cfg(test)]
+mod test_dispatch {
+    use crate::{Enumerable, IndexConst, O10, O16, O6};
+
+    use super::*;
+
+    #[test]
+    fn test_backends() {
+        let vector = |lanes| {
+            if simd_width() > 0 {
+                CheckBackend::Vector { lanes }
+            } else {
+                CheckBackend::Scalar
+            }
+        };
+        let widest = (simd_width() / 32).max(4);
+
+        assert_eq!(Permutation::<O3>::backend(), CheckBackend::FixedScalar);
+        assert_eq!(Construction::<O4>::backend(), CheckBackend::FixedScalar);
+        assert_eq!(Square::<O5>::backend(), vector(widest.min(8)));
+        assert_eq!(Square::<O10>::backend(), vector(widest.min(16)));
+        assert_eq!(vector_lanes(3), 4);
+        assert_eq!(
+            CheckBackend::Vector { lanes: 16 }.to_string(),
+            "vector (16 lanes)"
+        );
+    }
+
+    #[test]
+    fn test_every_backend() {
+        let backends = [
+            CheckBackend::FixedScalar,
+            CheckBackend::Scalar,
+            CheckBackend::FixedVector,
+            CheckBackend::Vector {
+                lanes: vector_lanes(3),
+            },
+        ];
+
+        for k in 0..O3::MAX_INDEX {
+            let a = Permutation::<O3>::kth(k);
+            let expected = a.check_n_s().is_some();
+            for backend in backends {
+                assert_eq!(check_data::<O3>(&a.square.data, backend), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let a = Construction::<O5>::siamese(2);
+        let mut b = a.clone();
+        b.square.data.swap(0, 1);
+
+        assert_eq!(a.check(), Some(a.clone()));
+        assert_eq!(b.check(), None);
+        assert_eq!(a.square.check(), Some(a.square.clone()));
+
+        let c = Permutation::<O4>::kth(80_867_885_530);
+        assert_eq!(c.check(), Some(c));
+        assert_eq!(c.square.check(), Some(c.square));
+        let compact = CompactPermutation::try_from(c).unwrap();
+        assert_eq!(compact.check(), Some(compact));
+        let packed = PackedPerm16::try_from(c).unwrap();
+        assert_eq!(packed.check(), Some(packed));
+
+        let d = Construction::<O6>::identity();
+        assert_eq!(d.check(), None);
+        assert_eq!(Construction::<O16>::zeros().check(), None);
+
+        let e = VecSquare::<O6>::from_vec(vec![1; 35]);
+        assert_eq!(e.check(), None);
+    }
+}
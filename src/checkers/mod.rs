@@ -1,5 +1,7 @@
+mod dispatch;
 mod scalar;
 mod vectorized;
 
+pub use dispatch::*;
 pub use scalar::*;
 pub use vectorized::*;
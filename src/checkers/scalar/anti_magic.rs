@@ -0,0 +1,74 @@
+use crate::{Construction, Params, Permutation};
+
+use super::check::constraint_vector_sums;
+
+macro_rules! impl_anti_magic_checker_for_type {
+    ($t:tt) => {
+        impl<P: Params + Copy> $t<P>
+        where
+            [(); P::ELEMENTS]:,
+        {
+            /// Returns the sums of the rows, columns and both diagonals of a square, sorted.
+            #[inline]
+            #[must_use]
+            pub fn line_sums(&self) -> Vec<u32> {
+                let mut sums = constraint_vector_sums::<P>(&self.square.data);
+                sums.sort_unstable();
+                sums
+            }
+
+            /// Check if a square is a heterosquare, with the sums of its rows, columns and both
+            /// diagonals all distinct. Returns the sorted sums if it is.
+            #[inline]
+            #[must_use]
+            pub fn check_hetero_n_s(&self) -> Option<Vec<u32>> {
+                let sums = self.line_sums();
+                sums.windows(2).all(|w| w[0] != w[1]).then_some(sums)
+            }
+
+            /// Check if a square is anti-magic, with the sums of its rows, columns and both
+            /// diagonals forming a run of consecutive numbers. Returns the sorted sums if it is.
+            #[inline]
+            #[must_use]
+            pub fn check_anti_magic_n_s(&self) -> Option<Vec<u32>> {
+                let sums = self.line_sums();
+                sums.windows(2).all(|w| w[0] + 1 == w[1]).then_some(sums)
+            }
+        }
+    };
+}
+
+impl_anti_magic_checker_for_type!(Construction);
+impl_anti_magic_checker_for_type!(Permutation);
+
+#[cfg(test)]
+mod test_anti_magic {
+    use crate::{Construction, O3, O4};
+
+    #[test]
+    fn test_heterosquare() {
+        let a = Construction::<O3>::try_from([1, 2, 3, 8, 9, 4, 7, 6, 5].as_slice()).unwrap();
+        assert_eq!(
+            a.check_hetero_n_s(),
+            Some(vec![6, 12, 15, 16, 17, 18, 19, 21])
+        );
+    }
+
+    #[test]
+    fn test_anti_magic() {
+        let a = Construction::<O4>::try_from(
+            [2, 15, 5, 13, 16, 3, 7, 12, 9, 8, 14, 1, 6, 4, 11, 10].as_slice(),
+        )
+        .unwrap();
+        assert_eq!(a.check_anti_magic_n_s(), Some((29..=38).collect()));
+        assert!(a.check_hetero_n_s().is_some());
+
+        let b = Construction::<O4>::try_from(
+            [16, 3, 2, 13, 5, 10, 11, 8, 9, 6, 7, 12, 4, 15, 14, 1].as_slice(),
+        )
+        .unwrap();
+        assert_eq!(b.line_sums(), vec![34; 10]);
+        assert!(b.check_hetero_n_s().is_none());
+        assert!(b.check_anti_magic_n_s().is_none());
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{Construction, Params, Permutation, Square, VecSquare, O3, O4};
+use std::iter::Sum;
+use std::ops::AddAssign;
+
+use crate::{
+    Cell, CompactPermutation, Construction, Params, Permutation, Square, VecSquare, O3, O4,
+};
+
 pub trait CheckScalar {
     type Output;
 
@@ -280,36 +286,102 @@ impl CheckScalar for VecSquare<O4> {
     }
 }
 
+/// Checks that every column of `values`, read as rows of `P::ORDER` entries, sums to
+/// `magic_sum`. A single row of entries is checked as one constraint vector.
 #[inline]
-fn sum_constraint_vectors<P: Params>(values: &[u32]) -> Option<()>
+pub(crate) fn sum_constraint_vectors<P: Params, T>(values: &[T], magic_sum: T) -> Option<()>
 where
     [(); P::ORDER]:,
+    T: Copy + Default + PartialEq + AddAssign + Sum<T>,
 {
     let mut chunks = values.chunks_exact(P::ORDER);
     assert!(chunks.remainder().is_empty());
 
     if chunks.len() == 1 {
-        return if chunks.next()?.iter().sum::<u32>() == P::MAGIC_SUM {
+        return if chunks.next()?.iter().copied().sum::<T>() == magic_sum {
             Some(())
         } else {
             None
         };
     }
 
-    let sums = chunks.fold([0; P::ORDER], |mut acc, chunk| {
+    let sums = chunks.fold([T::default(); P::ORDER], |mut acc, chunk| {
         for i in 0..P::ORDER {
             acc[i] += chunk[i];
         }
         acc
     });
 
-    if sums.into_iter().all(|sum| sum == P::MAGIC_SUM) {
+    if sums.into_iter().all(|sum| sum == magic_sum) {
         Some(())
     } else {
         None
     }
 }
 
+/// Returns the first cell of every row, column and diagonal of a square of order `n`, and the
+/// step to the next cell of the line.
+pub(crate) fn line_starts(n: usize) -> impl Iterator<Item = (usize, usize)> + Clone {
+    (0..n)
+        .map(move |r| (r * n, 1))
+        .chain((0..n).map(move |c| (c, n)))
+        .chain([(0, n + 1), (n - 1, n - 1)])
+}
+
+/// Splits the entries of a square into the constraint vectors summed by
+/// `sum_constraint_vectors`: the entries in order, whose columns are the columns of the square,
+/// the entries transposed, whose columns are the rows, and both diagonals.
+#[inline]
+pub(crate) fn constraint_vectors<P: Params>(data: &[u32]) -> [Vec<u32>; 4] {
+    let (r, c): (Vec<u32>, Vec<u32>) = (0..P::ELEMENTS)
+        .map(|e| e / P::ORDER)
+        .zip((0usize..P::ELEMENTS).map(|s| s % P::ORDER))
+        .map(|(i, a)| (data[i * P::ORDER + a], data[a * P::ORDER + i]))
+        .unzip();
+
+    let t1: Vec<u32> = (0..P::ORDER).map(|a| data[a * (P::ORDER + 1)]).collect();
+    let t2: Vec<u32> = (0..P::ORDER)
+        .map(|a| data[(a + 1) * (P::ORDER - 1)])
+        .collect();
+
+    [r, c, t1, t2]
+}
+
+/// Returns the sum of every constraint vector of a square, in the order of
+/// `constraint_vectors`: the columns, the rows, then both diagonals.
+pub(crate) fn constraint_vector_sums<P: Params>(data: &[u32]) -> Vec<u32> {
+    constraint_vectors::<P>(data)
+        .iter()
+        .flat_map(|values| {
+            let chunks = values.chunks_exact(P::ORDER);
+            if chunks.len() == 1 {
+                return vec![values.iter().sum()];
+            }
+
+            chunks.fold(vec![0; P::ORDER], |mut acc, chunk| {
+                acc.iter_mut().zip(chunk).for_each(|(s, &e)| *s += e);
+                acc
+            })
+        })
+        .collect()
+}
+
+/// Checks that every constraint vector of the entries of a square sums to `P::MAGIC_SUM`.
+#[inline]
+pub(crate) fn check_n_s_data<P: Params>(data: &[u32]) -> Option<()>
+where
+    [(); P::ORDER]:,
+{
+    let [r, c, t1, t2] = constraint_vectors::<P>(data);
+
+    sum_constraint_vectors::<P, _>(&r, P::MAGIC_SUM)?;
+    sum_constraint_vectors::<P, _>(&c, P::MAGIC_SUM)?;
+    sum_constraint_vectors::<P, _>(&t1, P::MAGIC_SUM)?;
+    sum_constraint_vectors::<P, _>(&t2, P::MAGIC_SUM)?;
+
+    Some(())
+}
+
 // Reduce code duplication
 //-------------------------------------------------------------------------------------------------
 
@@ -324,29 +396,7 @@ macro_rules! impl_generic_scalar_checker_for_type {
             #[inline]
             #[must_use]
             pub fn check_n_s(&self) -> Option<$t<P>> {
-                let (r, c): (Vec<u32>, Vec<u32>) = (0..P::ELEMENTS)
-                    .map(|e| e / P::ORDER)
-                    .zip((0usize..P::ELEMENTS).map(|s| s % P::ORDER))
-                    .map(|(i, a)| {
-                        (
-                            self.square.data[i * P::ORDER + a],
-                            self.square.data[a * P::ORDER + i],
-                        )
-                    })
-                    .unzip();
-
-                let t1: Vec<u32> = (0..P::ORDER)
-                    .map(|a| self.square.data[a * (P::ORDER + 1)])
-                    .collect();
-                let t2: Vec<u32> = (0..P::ORDER)
-                    .map(|a| self.square.data[(a + 1) * (P::ORDER - 1)])
-                    .collect();
-
-                sum_constraint_vectors(&r)?;
-                sum_constraint_vectors(&c)?;
-                sum_constraint_vectors(&t1)?;
-                sum_constraint_vectors(&t2)?;
-
+                check_n_s_data::<P>(&self.square.data)?;
                 Some((*self).clone())
             }
         }
@@ -356,6 +406,27 @@ macro_rules! impl_generic_scalar_checker_for_type {
 impl_generic_scalar_checker_for_type!(Construction);
 impl_generic_scalar_checker_for_type!(Permutation);
 
+impl<P: Params + Copy> CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Check if a compact permutation of any size is magic. Each line is summed straight from
+    /// its cells, without widening the whole square to `u32` first.
+    #[inline]
+    #[must_use]
+    pub fn check_n_s(&self) -> Option<Self> {
+        let n = P::ORDER;
+        line_starts(n)
+            .all(|(start, step)| {
+                (0..n)
+                    .map(|k| self.data[start + k * step].to_u32())
+                    .sum::<u32>()
+                    == P::MAGIC_SUM
+            })
+            .then_some(*self)
+    }
+}
+
 //-------------------------------------------------------------------------------------------------
 
 // Reduce code duplication
@@ -404,7 +475,7 @@ impl_check_scalar_for_type_with_param!(Construction, O4);
 #[cfg(test)]
 mod check_tests {
     use super::*;
-    use crate::{Enumerable, ParameterSetError};
+    use crate::{Enumerable, IndexConst, ParameterSetError, O15};
 
     #[test]
     fn test_safe_3() -> Result<(), ParameterSetError> {
@@ -481,4 +552,21 @@ mod check_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_check_n_s_compact() -> Result<(), ParameterSetError> {
+        for k in 0..O3::MAX_INDEX {
+            let a = Permutation::<O3>::kth(k);
+            let b = CompactPermutation::try_from(a)?;
+            assert_eq!(b.check_n_s().is_some(), a.check_n_s().is_some());
+        }
+
+        let c = Construction::<O15>::siamese(7);
+        let mut d = CompactPermutation::<O15>::try_from(c.square.data.as_slice())?;
+        assert_eq!(d.check_n_s(), Some(d));
+        d.data.swap(0, 1);
+        assert_eq!(d.check_n_s(), None);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,55 @@
+use crate::MagicFigure;
+
+impl MagicFigure {
+    /// Check if an assignment of entries to the cells of a figure is magic, returning the sum
+    /// every line shares.
+    #[inline]
+    #[must_use]
+    pub fn check_s(&self, values: &[i64]) -> Option<i64> {
+        if values.len() != self.cells() {
+            return None;
+        }
+
+        let mut sums = self
+            .lines()
+            .iter()
+            .map(|line| line.iter().map(|&c| values[c]).sum::<i64>());
+        let first = sums.next()?;
+
+        sums.all(|s| s == first).then_some(first)
+    }
+}
+
+#[cfg(test)]
+mod test_figure {
+    use crate::{Construction, NonNormalSquare, O4};
+
+    use super::*;
+
+    #[test]
+    fn test_square() {
+        let figure = MagicFigure::square::<O4>();
+        let a = NonNormalSquare::from(
+            &Construction::<O4>::try_from(
+                [1, 2, 15, 16, 12, 14, 3, 5, 13, 7, 10, 4, 8, 11, 6, 9].as_slice(),
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(figure.check_s(&a.data), Some(34));
+        assert_eq!(figure.check_s(&(1..=16).collect::<Vec<_>>()), None);
+        assert_eq!(figure.check_s(&[1; 9]), None);
+    }
+
+    #[test]
+    fn test_edge_magic() {
+        let triangle = MagicFigure::edge_magic(3, &[(0, 1), (1, 2), (2, 0)]).unwrap();
+
+        assert_eq!(triangle.check_s(&[1, 3, 5, 6, 2, 4]), Some(10));
+        assert_eq!(triangle.check_s(&[1, 2, 3, 4, 5, 6]), None);
+        assert_eq!(
+            MagicFigure::hexagram().check_s(&[1, 3, 2, 4, 9, 5, 11, 8, 7, 10, 6, 12]),
+            Some(26)
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{franklin_lines, Construction, Params};
+
+impl<P: Params + Copy> Construction<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Check if a square is a Franklin square: every row, column and bent diagonal sums to
+    /// `P::MAGIC_SUM`, every half row and half column to half of it, and every 2×2 subsquare,
+    /// wrapping around the edges, to four times the mean entry. The main diagonals are not
+    /// checked. Orders which are not a multiple of 4 have no Franklin squares.
+    #[inline]
+    #[must_use]
+    pub fn check_franklin_n_s(&self) -> Option<Self> {
+        if P::ORDER % 4 != 0 {
+            return None;
+        }
+
+        let total = P::ELEMENTS as u64 + 1;
+        franklin_lines::<P>()
+            .iter()
+            .all(|line| {
+                let sum = line
+                    .iter()
+                    .map(|&c| u64::from(self.square.data[c]))
+                    .sum::<u64>();
+                2 * sum == line.len() as u64 * total
+            })
+            .then(|| self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test_franklin {
+    use crate::{Construction, O4, O8};
+
+    #[test]
+    fn test_franklin() {
+        let a = Construction::<O8>::franklin();
+        assert!(a.check_franklin_n_s().is_some());
+        assert!(a.check_n_s().is_none());
+
+        let mut b = a.clone();
+        b.square.data.swap(0, 1);
+        assert!(b.check_franklin_n_s().is_none());
+    }
+
+    #[test]
+    fn test_order_4() {
+        let a = Construction::<O4>::try_from(
+            [1, 8, 13, 12, 14, 11, 2, 7, 4, 5, 16, 9, 15, 10, 3, 6].as_slice(),
+        )
+        .unwrap();
+
+        assert!(a.check_franklin_n_s().is_none());
+        assert!(Construction::<O4>::identity()
+            .check_franklin_n_s()
+            .is_none());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{HyperParams, Hypercube, Params};
+
+impl<P: Params + Copy, const D: usize> Hypercube<P, D> {
+    /// Check if a hypercube is simple magic: every axis-parallel line and every main diagonal
+    /// sums to `LINE_SUM`.
+    #[inline]
+    #[must_use]
+    pub fn check_n_s(&self) -> Option<Self> {
+        self.lines_sum_to(&Self::constraint_lines(false))
+    }
+
+    /// Check if a hypercube is diagonal magic: simple magic, with the main diagonals of every
+    /// axis-aligned section also summing to `LINE_SUM`.
+    #[inline]
+    #[must_use]
+    pub fn check_diagonal_n_s(&self) -> Option<Self> {
+        self.lines_sum_to(&Self::constraint_lines(true))
+    }
+
+    /// Check if a hypercube is perfect magic: every pan-diagonal, broken or not, sums to
+    /// `LINE_SUM`. For `D = 2` this is a pandiagonal magic square.
+    #[inline]
+    #[must_use]
+    pub fn check_perfect_n_s(&self) -> Option<Self> {
+        self.lines_sum_to(&Self::pan_lines())
+    }
+
+    fn lines_sum_to(&self, lines: &[Vec<usize>]) -> Option<Self> {
+        let target = <P as HyperParams<D>>::LINE_SUM;
+        lines
+            .iter()
+            .all(|line| line.iter().map(|&c| u64::from(self.data[c])).sum::<u64>() == target)
+            .then(|| self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test_hypercube {
+    use crate::{Construction, O3, O4};
+
+    use super::*;
+
+    #[test]
+    fn test_square() {
+        let a = Hypercube::from(&Construction::<O3>::siamese(1));
+        assert!(a.check_n_s().is_some());
+        assert!(a.check_diagonal_n_s().is_some());
+        assert!(a.check_perfect_n_s().is_none());
+
+        let b = Hypercube::from(&Construction::<O3>::identity());
+        assert!(b.check_n_s().is_none());
+    }
+
+    #[test]
+    fn test_pandiagonal() {
+        let a = Hypercube::<O4, 2>::try_from(
+            [1, 8, 13, 12, 14, 11, 2, 7, 4, 5, 16, 9, 15, 10, 3, 6].as_slice(),
+        )
+        .unwrap();
+
+        assert!(a.check_n_s().is_some());
+        assert!(a.check_perfect_n_s().is_some());
+    }
+
+    #[test]
+    fn test_cube() {
+        let a = Hypercube::<O3, 3>::try_from(
+            [
+                8, 24, 10, 12, 7, 23, 22, 11, 9, 15, 1, 26, 25, 14, 3, 2, 27, 13, 19, 17, 6, 5, 21,
+                16, 18, 4, 20,
+            ]
+            .as_slice(),
+        )
+        .unwrap();
+
+        assert!(a.check_n_s().is_some());
+        assert!(a.check_diagonal_n_s().is_none());
+        assert!(a.check_perfect_n_s().is_none());
+    }
+}
@@ -1,3 +1,11 @@
+mod anti_magic;
 mod check;
+mod figure;
+mod franklin;
+mod hypercube;
+mod most_perfect;
+mod multiplicative;
+mod non_normal;
+mod rectangle;
 
 pub use check::*;
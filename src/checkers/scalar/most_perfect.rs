@@ -0,0 +1,62 @@
+use crate::{Construction, Params};
+
+impl<P: Params + Copy> Construction<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Check if a square is most-perfect: every 2×2 subsquare, wrapping around the edges, sums
+    /// to `2 * (P::ELEMENTS + 1)`, and entries half the order apart along a diagonal sum to
+    /// `P::ELEMENTS + 1`. Together these make the square pandiagonal.
+    #[inline]
+    #[must_use]
+    pub fn check_most_perfect_n_s(&self) -> Option<Self> {
+        let n = P::ORDER;
+        if n % 4 != 0 {
+            return None;
+        }
+
+        let a = |i: usize, j: usize| self.square.data[(i % n) * n + j % n];
+        let total = P::ELEMENTS as u32 + 1;
+        let cells = (0..n).flat_map(|i| (0..n).map(move |j| (i, j)));
+
+        let compact = cells
+            .clone()
+            .all(|(i, j)| a(i, j) + a(i, j + 1) + a(i + 1, j) + a(i + 1, j + 1) == 2 * total);
+        if !compact {
+            return None;
+        }
+
+        cells
+            .filter(|&(i, _)| i < n / 2)
+            .all(|(i, j)| a(i, j) + a(i + n / 2, j + n / 2) == total)
+            .then(|| self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test_most_perfect {
+    use crate::{Construction, O4, O8};
+
+    #[test]
+    fn test_order_4() {
+        let a = Construction::<O4>::try_from(
+            [1, 8, 13, 12, 14, 11, 2, 7, 4, 5, 16, 9, 15, 10, 3, 6].as_slice(),
+        )
+        .unwrap();
+        assert!(a.check_most_perfect_n_s().is_some());
+
+        let b = Construction::<O4>::try_from(
+            [16, 3, 2, 13, 5, 10, 11, 8, 9, 6, 7, 12, 4, 15, 14, 1].as_slice(),
+        )
+        .unwrap();
+        assert!(b.check_n_s().is_some());
+        assert!(b.check_most_perfect_n_s().is_none());
+    }
+
+    #[test]
+    fn test_franklin() {
+        assert!(Construction::<O8>::franklin()
+            .check_most_perfect_n_s()
+            .is_none());
+    }
+}
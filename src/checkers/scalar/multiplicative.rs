@@ -0,0 +1,191 @@
+use crate::{CheckScalar, MultiplicativeSquare, Params, O3, O4};
+
+/// Multiplies the entries of a line, returning `None` on overflow.
+#[inline]
+fn product(values: &[u128]) -> Option<u128> {
+    values.iter().try_fold(1u128, |acc, &x| acc.checked_mul(x))
+}
+
+/// Checks that every line has the same product as the first, treating overflow as a mismatch.
+#[inline]
+fn products_match(lines: &[&[u128]]) -> Option<()> {
+    let target = product(lines.first()?)?;
+    lines
+        .iter()
+        .all(|line| product(line) == Some(target))
+        .then_some(())
+}
+
+impl CheckScalar for MultiplicativeSquare<O3> {
+    type Output = Self;
+
+    #[inline]
+    unsafe fn check_s_unsafe(&self) -> Option<Self::Output> {
+        let a = *self.data.get_unchecked(0);
+        let b = *self.data.get_unchecked(1);
+        let c = *self.data.get_unchecked(2);
+        let d = *self.data.get_unchecked(3);
+        let e = *self.data.get_unchecked(4);
+        let f = *self.data.get_unchecked(5);
+        let g = *self.data.get_unchecked(6);
+        let h = *self.data.get_unchecked(7);
+        let i = *self.data.get_unchecked(8);
+
+        products_match(&[
+            &[a, b, c],
+            &[d, e, f],
+            &[g, h, i],
+            &[a, d, g],
+            &[b, e, h],
+            &[c, f, i],
+            &[a, e, i],
+            &[c, e, g],
+        ])?;
+
+        Some(self.clone())
+    }
+
+    fn check_s(&self) -> Option<Self::Output> {
+        let [a, b, c, d, e, f, g, h, i] = <[u128; 9]>::try_from(self.data.as_slice()).ok()?;
+
+        products_match(&[
+            &[a, b, c],
+            &[d, e, f],
+            &[g, h, i],
+            &[a, d, g],
+            &[b, e, h],
+            &[c, f, i],
+            &[a, e, i],
+            &[c, e, g],
+        ])?;
+
+        Some(self.clone())
+    }
+}
+
+impl CheckScalar for MultiplicativeSquare<O4> {
+    type Output = Self;
+
+    #[inline]
+    unsafe fn check_s_unsafe(&self) -> Option<Self::Output> {
+        let a = *self.data.get_unchecked(0);
+        let b = *self.data.get_unchecked(1);
+        let c = *self.data.get_unchecked(2);
+        let d = *self.data.get_unchecked(3);
+        let e = *self.data.get_unchecked(4);
+        let f = *self.data.get_unchecked(5);
+        let g = *self.data.get_unchecked(6);
+        let h = *self.data.get_unchecked(7);
+        let i = *self.data.get_unchecked(8);
+        let j = *self.data.get_unchecked(9);
+        let k = *self.data.get_unchecked(10);
+        let l = *self.data.get_unchecked(11);
+        let m = *self.data.get_unchecked(12);
+        let n = *self.data.get_unchecked(13);
+        let o = *self.data.get_unchecked(14);
+        let p = *self.data.get_unchecked(15);
+
+        products_match(&[
+            &[a, b, c, d],
+            &[e, f, g, h],
+            &[i, j, k, l],
+            &[m, n, o, p],
+            &[a, e, i, m],
+            &[b, f, j, n],
+            &[c, g, k, o],
+            &[d, h, l, p],
+            &[a, f, k, p],
+            &[d, g, j, m],
+        ])?;
+
+        Some(self.clone())
+    }
+
+    fn check_s(&self) -> Option<Self::Output> {
+        let [a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p] =
+            <[u128; 16]>::try_from(self.data.as_slice()).ok()?;
+
+        products_match(&[
+            &[a, b, c, d],
+            &[e, f, g, h],
+            &[i, j, k, l],
+            &[m, n, o, p],
+            &[a, e, i, m],
+            &[b, f, j, n],
+            &[c, g, k, o],
+            &[d, h, l, p],
+            &[a, f, k, p],
+            &[d, g, j, m],
+        ])?;
+
+        Some(self.clone())
+    }
+}
+
+impl<P: Params + Copy> MultiplicativeSquare<P> {
+    /// Check if a multiplicative square of any size is magic.
+    #[inline]
+    #[must_use]
+    pub fn check_n_s(&self) -> Option<Self> {
+        let n = P::ORDER;
+        let rows = (0..n).map(|r| (0..n).map(|c| self.data[r * n + c]).collect::<Vec<_>>());
+        let cols = (0..n).map(|c| (0..n).map(|r| self.data[r * n + c]).collect::<Vec<_>>());
+        let t1 = (0..n).map(|a| self.data[a * (n + 1)]).collect::<Vec<_>>();
+        let t2 = (0..n)
+            .map(|a| self.data[(a + 1) * (n - 1)])
+            .collect::<Vec<_>>();
+
+        let lines = rows.chain(cols).chain([t1, t2]).collect::<Vec<_>>();
+        products_match(&lines.iter().map(Vec::as_slice).collect::<Vec<_>>())?;
+
+        Some(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test_multiplicative {
+    use crate::{Construction, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_check_3() {
+        let a = MultiplicativeSquare::<O3>::try_from([2, 9, 12, 36, 6, 1, 3, 4, 18].as_slice())
+            .unwrap();
+        let b = MultiplicativeSquare::<O3>::try_from([2, 9, 12, 36, 6, 1, 3, 18, 4].as_slice())
+            .unwrap();
+
+        assert_eq!(a.check_s(), Some(a.clone()));
+        assert_eq!(unsafe { a.check_s_unsafe() }, Some(a.clone()));
+        assert_eq!(a.check_n_s(), Some(a.clone()));
+        assert_eq!(b.check_s(), None);
+        assert_eq!(b.check_n_s(), None);
+    }
+
+    #[test]
+    fn test_check_4() {
+        let c = Construction::<O4>::try_from(
+            [1, 2, 15, 16, 12, 14, 3, 5, 13, 7, 10, 4, 8, 11, 6, 9].as_slice(),
+        )
+        .unwrap();
+        let a = MultiplicativeSquare::exponentiate(&c, 3).unwrap();
+
+        assert!(a.check_s().is_some());
+        assert!(unsafe { a.check_s_unsafe() }.is_some());
+        assert!(a.check_n_s().is_some());
+
+        let b = MultiplicativeSquare::exponentiate(&Construction::<O4>::identity(), 3).unwrap();
+        assert!(b.check_s().is_none());
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Every line overflows, so no two products can be compared.
+        let a = MultiplicativeSquare::<O3>::new(vec![u128::MAX; 9]).unwrap();
+        assert_eq!(a.check_s(), None);
+        assert_eq!(a.check_n_s(), None);
+
+        let b = MultiplicativeSquare::exponentiate(&Construction::<O5>::siamese(2), 2).unwrap();
+        assert!(b.check_n_s().is_some());
+    }
+}
@@ -0,0 +1,78 @@
+use itertools::Itertools;
+
+use crate::{NonNormalSquare, Params};
+
+use super::check::sum_constraint_vectors;
+
+impl<P: Params + Copy> NonNormalSquare<P>
+where
+    [(); P::ORDER]:,
+{
+    /// Check if the square is magic with respect to its own `magic_sum`.
+    #[inline]
+    #[must_use]
+    pub fn check_n_s(&self) -> Option<Self> {
+        let n = P::ORDER;
+        let r = self.data.clone();
+        let c = (0..P::ELEMENTS)
+            .map(|e| self.data[(e % n) * n + e / n])
+            .collect_vec();
+        let t1 = (0..n).map(|a| self.data[a * (n + 1)]).collect_vec();
+        let t2 = (0..n).map(|a| self.data[(a + 1) * (n - 1)]).collect_vec();
+
+        sum_constraint_vectors::<P, _>(&r, self.magic_sum)?;
+        sum_constraint_vectors::<P, _>(&c, self.magic_sum)?;
+        sum_constraint_vectors::<P, _>(&t1, self.magic_sum)?;
+        sum_constraint_vectors::<P, _>(&t2, self.magic_sum)?;
+
+        Some(self.clone())
+    }
+
+    /// Check if the square is magic and its entries are exactly the multiset `entries`.
+    #[must_use]
+    pub fn check_entries_n_s(&self, entries: &[i64]) -> Option<Self> {
+        if self.entries() != entries.iter().copied().sorted_unstable().collect_vec() {
+            return None;
+        }
+
+        self.check_n_s()
+    }
+}
+
+#[cfg(test)]
+mod test_non_normal {
+    use crate::{Construction, O3, O4, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_primes_3() {
+        let a = NonNormalSquare::<O3>::new(vec![17, 89, 71, 113, 59, 5, 47, 29, 101], 177).unwrap();
+        assert_eq!(Some(a.clone()), a.check_n_s());
+
+        let primes = [5, 17, 29, 47, 59, 71, 89, 101, 113];
+        assert!(a.check_entries_n_s(&primes).is_some());
+        assert!(a.check_entries_n_s(&[1, 2, 3, 4, 5, 6, 7, 8, 9]).is_none());
+
+        let b = NonNormalSquare::<O3>::new(a.data.clone(), 178).unwrap();
+        assert_eq!(b.check_n_s(), None);
+    }
+
+    #[test]
+    fn test_lucas() {
+        for (a, b, c) in [(1, 3, 5), (2, 7, -4), (5, 3, 100)] {
+            assert!(NonNormalSquare::<O3>::lucas(a, b, c).check_n_s().is_some());
+        }
+    }
+
+    #[test]
+    fn test_construction() {
+        let a = NonNormalSquare::from(&Construction::<O5>::siamese(2));
+        assert!(a.check_n_s().is_some());
+        assert!(a.affine(-2, 30).check_n_s().is_some());
+
+        let b = NonNormalSquare::<O4>::new(vec![3; 16], 12).unwrap();
+        assert!(b.check_n_s().is_some());
+        assert!(!b.is_distinct());
+    }
+}
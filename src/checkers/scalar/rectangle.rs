@@ -0,0 +1,70 @@
+use crate::{MagicRectangle, RectParams};
+
+impl<R: RectParams + Copy> MagicRectangle<R> {
+    /// Check if a rectangle is magic: it holds each of `1..=R::ROWS * R::COLS` once, every row
+    /// sums to `R::ROW_SUM` and every column to `R::COL_SUM`.
+    #[inline]
+    #[must_use]
+    pub fn check_n_s(&self) -> Option<Self> {
+        let mut seen = vec![false; self.data.len()];
+        let normal = self.data.iter().all(|&x| {
+            let slot = (x as usize).checked_sub(1).and_then(|i| seen.get_mut(i));
+            slot.is_some_and(|seen| !std::mem::replace(seen, true))
+        });
+        if !normal {
+            return None;
+        }
+
+        let rows = (0..R::ROWS).all(|i| self.row(i).iter().sum::<u32>() == R::ROW_SUM);
+        let cols = (0..R::COLS).all(|j| {
+            let column = self.data.iter().skip(j).step_by(R::COLS);
+            column.sum::<u32>() == R::COL_SUM
+        });
+
+        (rows && cols).then(|| self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test_rectangle {
+    use crate::{Construction, R2x4, R3x5, O3};
+
+    use super::*;
+
+    #[test]
+    fn test_rectangle() {
+        let a = MagicRectangle::<R3x5>::try_from(
+            [2, 15, 6, 12, 5, 14, 4, 10, 3, 9, 8, 5, 8, 9, 11].as_slice(),
+        )
+        .unwrap();
+        assert!(a.check_n_s().is_none());
+
+        let d = MagicRectangle::<R3x5>::try_from(
+            [15, 13, 3, 4, 5, 8, 9, 10, 6, 7, 1, 2, 11, 14, 12].as_slice(),
+        )
+        .unwrap();
+        assert!(d.check_n_s().is_some());
+
+        // Every row sums to 18 and every column to 9, but the entries repeat.
+        let e = MagicRectangle::<R2x4>::try_from([5, 4, 5, 4, 4, 5, 4, 5].as_slice()).unwrap();
+        assert!(e.check_n_s().is_none());
+
+        let b = MagicRectangle::<R2x4>::try_from([5, 3, 2, 8, 4, 6, 7, 1].as_slice()).unwrap();
+        assert!(b.check_n_s().is_some());
+
+        let c = MagicRectangle::<R2x4>::try_from([1, 2, 3, 4, 5, 6, 7, 8].as_slice()).unwrap();
+        assert!(c.check_n_s().is_none());
+    }
+
+    #[test]
+    fn test_square() {
+        assert!(MagicRectangle::from(&Construction::<O3>::siamese(1))
+            .check_n_s()
+            .is_some());
+
+        // Semi-magic: the rows and columns sum to 15 but the diagonals do not.
+        let a = MagicRectangle::<O3>::try_from([1, 5, 9, 6, 7, 2, 8, 3, 4].as_slice()).unwrap();
+        assert!(a.check_n_s().is_some());
+        assert!(Construction::<O3>::try_from(a.data.as_slice()).is_ok());
+    }
+}
@@ -0,0 +1,134 @@
+use std::simd::prelude::*;
+
+use crate::{line_starts, Params, Permutation};
+
+/// The number of squares `check_batch_v` checks side by side, one in each SIMD lane.
+pub const BATCH_LANES: usize = 8;
+
+/// Check many squares at once. Bit `i % 64` of `mask[i / 64]` is set if `squares[i]` is magic,
+/// and cleared otherwise.
+///
+/// The squares are transposed `BATCH_LANES` at a time into structure-of-arrays form: a vector
+/// for every cell, holding that cell of a different square in each lane. Each constraint vector
+/// is then summed for all of the squares with one addition per cell, and a group is dropped as
+/// soon as none of its squares can still be magic. Lanes past the last square hold zeros, which
+/// are never magic.
+///
+/// # Panics
+/// - If `mask` holds fewer bits than there are squares.
+#[inline]
+pub fn check_batch_v<P: Params>(squares: &[Permutation<P>], mask: &mut [u64])
+where
+    [(); P::ELEMENTS]:,
+{
+    assert!(
+        mask.len() * 64 >= squares.len(),
+        "Mask must hold a bit for every square!"
+    );
+    mask[..squares.len().div_ceil(64)].fill(0);
+
+    let n = P::ORDER;
+    let magic = Simd::splat(P::MAGIC_SUM);
+    let lines = line_starts(n);
+
+    let mut cells = [Simd::<u32, BATCH_LANES>::splat(0); P::ELEMENTS];
+    for (g, group) in squares.chunks(BATCH_LANES).enumerate() {
+        for (e, cell) in cells.iter_mut().enumerate() {
+            *cell = Simd::from_array(std::array::from_fn(|s| {
+                group.get(s).map_or(0, |p| p.square.data[e])
+            }));
+        }
+
+        let mut alive = Mask::splat(true);
+        for (start, step) in lines.clone() {
+            let sum = (0..n).fold(Simd::<u32, BATCH_LANES>::splat(0), |acc, k| {
+                acc.saturating_add(cells[start + k * step])
+            });
+            alive &= sum.simd_eq(magic);
+            if !alive.any() {
+                break;
+            }
+        }
+
+        let at = g * BATCH_LANES;
+        mask[at / 64] |= alive.to_bitmask() << (at % 64);
+    }
+}
+
+/// Returns the magic squares among `squares`, in order, found with `check_batch_v`.
+#[must_use]
+pub fn magic_batch_v<P: Params + Copy>(squares: &[Permutation<P>]) -> Vec<Permutation<P>>
+where
+    [(); P::ELEMENTS]:,
+{
+    let mut mask = vec![0; squares.len().div_ceil(64)];
+    check_batch_v(squares, &mut mask);
+
+    squares
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| mask[i / 64] >> (i % 64) & 1 == 1)
+        .map(|(_, &p)| p)
+        .collect()
+}
+
+#[cfg(test)]
+mod test_batch {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{Enumerable, IndexConst, O3, O4, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_order_3() {
+        let squares = (0..O3::MAX_INDEX)
+            .map(Permutation::<O3>::kth)
+            .collect::<Vec<_>>();
+        let expected = squares
+            .iter()
+            .filter_map(Permutation::check_n_s)
+            .collect::<Vec<_>>();
+
+        assert_eq!(expected.len(), 8);
+        assert_eq!(magic_batch_v(&squares), expected);
+    }
+
+    #[test]
+    fn test_mask() {
+        let magic = Permutation::<O4>::kth(80_867_885_530);
+        let other = Permutation::<O4>::identity();
+        let squares = (0..131)
+            .map(|i| if i % 10 == 3 { magic } else { other })
+            .collect::<Vec<_>>();
+
+        let mut mask = [u64::MAX; 4];
+        check_batch_v(&squares, &mut mask);
+
+        for (i, p) in squares.iter().enumerate() {
+            assert_eq!(mask[i / 64] >> (i % 64) & 1 == 1, p.check_n_s().is_some());
+        }
+        assert_eq!(mask[3], u64::MAX);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let magic = crate::Construction::<O5>::siamese(2);
+        let squares = (0..500)
+            .map(|_| {
+                let mut data = magic.square.data.clone();
+                if rng.gen_bool(0.5) {
+                    data.swap(rng.gen_range(0..25), rng.gen_range(0..25));
+                }
+                Permutation::<O5>::try_from(data.as_slice()).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let expected = squares
+            .iter()
+            .filter_map(Permutation::check_n_s)
+            .collect::<Vec<_>>();
+        assert_eq!(magic_batch_v(&squares), expected);
+    }
+}
@@ -1,7 +1,7 @@
-use crate::{Construction, Params, Permutation, Square, VecSquare, O3, O4};
-use itertools::Itertools;
+use crate::{
+    simd_width, Construction, PackedPerm16, Params, Permutation, Square, VecSquare, O3, O4, O5,
+};
 use std::simd::prelude::*;
-use std::simd::{LaneCount, SupportedLaneCount};
 
 pub trait CheckVector {
     type Output;
@@ -301,6 +301,129 @@ impl CheckVector for VecSquare<O4> {
     }
 }
 
+impl CheckVector for Square<O5> {
+    type Output = Self;
+
+    #[inline]
+    unsafe fn check_v_unsafe(&self) -> Option<Self> {
+        let mut tail = [0; 16];
+        tail[..9].copy_from_slice(self.get_unchecked(16..25));
+
+        let vals: [Simd<u32, 16_usize>; 2] = [
+            Simd::from_slice(self.get_unchecked(..16)),
+            Simd::from_array(tail),
+        ];
+
+        sum_five_v(vals).then_some(*self)
+    }
+
+    /// # Panics
+    /// - If the value returned by `get` is `None`
+    #[inline]
+    fn check_v(&self) -> Option<Self> {
+        let mut tail = [0; 16];
+        tail[..9].copy_from_slice(self.get(16..25).unwrap());
+
+        let vals: [Simd<u32, 16_usize>; 2] = [
+            Simd::from_slice(self.get(..16).unwrap()),
+            Simd::from_array(tail),
+        ];
+
+        sum_five_v(vals).then_some(*self)
+    }
+}
+
+impl CheckVector for VecSquare<O5> {
+    type Output = Self;
+
+    #[inline]
+    unsafe fn check_v_unsafe(&self) -> Option<Self> {
+        let mut tail = [0; 16];
+        tail[..9].copy_from_slice(self.data.get_unchecked(16..25));
+
+        let vals: [Simd<u32, 16_usize>; 2] = [
+            Simd::from_slice(self.data.get_unchecked(..16)),
+            Simd::from_array(tail),
+        ];
+
+        sum_five_v(vals).then_some((*self).clone())
+    }
+
+    /// # Panics
+    /// - If the value returned by `get` is `None`
+    #[inline]
+    fn check_v(&self) -> Option<Self> {
+        let mut tail = [0; 16];
+        tail[..9].copy_from_slice(self.data.get(16..25).unwrap());
+
+        let vals: [Simd<u32, 16_usize>; 2] = [
+            Simd::from_slice(self.data.get(..16).unwrap()),
+            Simd::from_array(tail),
+        ];
+
+        sum_five_v(vals).then_some((*self).clone())
+    }
+}
+
+/// Sums the 12 constraint vectors of an order 5 square, whose entries are loaded into two
+/// vectors with zeros after the last entry, in the first 12 lanes: the rows, the columns and both
+/// diagonals. The last 4 lanes add up zeros.
+#[inline(always)]
+fn sum_five_v(vals: [Simd<u32, 16_usize>; 2]) -> bool {
+    const VMASK: Simd<u32, 16_usize> = Simd::from_array([
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        O5::MAGIC_SUM,
+        0,
+        0,
+        0,
+        0,
+    ]);
+
+    let [v0, v1] = vals;
+    let mut v_a = simd_swizzle!(
+        v0,
+        v1,
+        [0, 5, 10, 15, 20, 0, 1, 2, 3, 4, 0, 4, 25, 25, 25, 25]
+    );
+    let v_b = simd_swizzle!(
+        v0,
+        v1,
+        [1, 6, 11, 16, 21, 5, 6, 7, 8, 9, 6, 8, 25, 25, 25, 25]
+    );
+    let v_c = simd_swizzle!(
+        v0,
+        v1,
+        [2, 7, 12, 17, 22, 10, 11, 12, 13, 14, 12, 12, 25, 25, 25, 25]
+    );
+    let v_d = simd_swizzle!(
+        v0,
+        v1,
+        [3, 8, 13, 18, 23, 15, 16, 17, 18, 19, 18, 16, 25, 25, 25, 25]
+    );
+    let v_e = simd_swizzle!(
+        v0,
+        v1,
+        [4, 9, 14, 19, 24, 20, 21, 22, 23, 24, 24, 20, 25, 25, 25, 25]
+    );
+
+    v_a = v_a.saturating_add(v_b);
+    v_a = v_a.saturating_add(v_c);
+    v_a = v_a.saturating_add(v_d);
+    v_a = v_a.saturating_add(v_e);
+
+    v_a.simd_eq(VMASK).all()
+}
+
 // Reduce code duplication
 //-------------------------------------------------------------------------------------------------
 
@@ -338,110 +461,125 @@ macro_rules! impl_check_vector_for_type_with_param {
 
 impl_check_vector_for_type_with_param!(Permutation, O3);
 impl_check_vector_for_type_with_param!(Permutation, O4);
+impl_check_vector_for_type_with_param!(Permutation, O5);
 impl_check_vector_for_type_with_param!(Construction, O3);
 impl_check_vector_for_type_with_param!(Construction, O4);
+impl_check_vector_for_type_with_param!(Construction, O5);
 
-//-------------------------------------------------------------------------------------------------
+/// The entries of a packed permutation are `0..=15`, so its lines sum to `O4::MAGIC_SUM - 4`.
+impl CheckVector for PackedPerm16 {
+    type Output = Self;
 
-impl<P: Params + Copy> Construction<P>
-where
-    [(); P::ELEMENTS]:,
-{
-    /// Check if a square-like object of any size is magic.
-    ///
-    /// `B`: represents the `LaneCount` for the SIMD arrays and can be though of the number of items
-    /// taken from the contraint vectors at each step to perform the whole summation, similar to
-    /// taking chunks of an iterator.
-    ///
-    /// # Caution
-    /// This method has not been tested for all cases and/or does not output correctly for certain
-    /// values of `B`. If accuracy is desired over throughput, use the `check_s_v` method.
+    /// A packed permutation always holds sixteen entries, so this is `check_v`.
     #[inline]
-    #[must_use]
-    pub fn check_n_v<const B: usize>(&self) -> Option<Self>
-    where
-        LaneCount<B>: SupportedLaneCount,
-    {
-        // Decompose the square data into two iterables.
-        //
-        // r: iterator over the joined rows of the square
-        // c: iterator over the joined cols of the square
-        let (r, c): (Vec<u32>, Vec<u32>) = (0..P::ELEMENTS)
-            .map(|e| e / P::ORDER)
-            .zip((0usize..P::ELEMENTS).map(|s| s % P::ORDER))
-            .map(|(i, a)| {
-                (
-                    self.square.data[i * P::ORDER + a],
-                    self.square.data[a * P::ORDER + i],
-                )
-            })
-            .unzip();
-
-        // Chunk over the row, col iterators so that we can iterate over whole rows and whole cols.
-        let rows = r.chunks_exact(P::ORDER).collect_vec();
-        let cols = c.chunks_exact(P::ORDER).collect_vec();
-
-        // Iterators over the left and right trace
-        let t1: Vec<u32> = (0..P::ORDER)
-            .map(|a| self.square.data[a * (P::ORDER + 1)])
-            .collect();
-        let t2: Vec<u32> = (0..P::ORDER)
-            .map(|a| self.square.data[(a + 1) * (P::ORDER - 1)])
-            .collect();
-
-        // Number of zeros to pad on the end of the SIMD arrays.
-        let pad = if P::CONSTRAINT_VECTORS.is_power_of_two() {
-            0
-        } else {
-            B.abs_diff(P::CONSTRAINT_VECTORS)
-        };
+    unsafe fn check_v_unsafe(&self) -> Option<Self::Output> {
+        self.check_v()
+    }
 
-        // Allocate the arrays used for summing.
-        let mut buffs = Vec::with_capacity(P::ORDER);
-        for _ in 0..P::ORDER {
-            buffs.push(Vec::<u32>::with_capacity(B));
-        }
+    #[inline]
+    fn check_v(&self) -> Option<Self::Output> {
+        const MAGIC: u8 = (O4::MAGIC_SUM - 4) as u8;
+
+        // Adds lanes `i`, `i + 4`, `i + 8` and `i + 12` into lane `i` and checks the first
+        // `lines` of them, which are the columns of the square held in `v`.
+        let check = |v: u8x16, lines: u64| {
+            let v = v + v.rotate_elements_left::<8>();
+            let v = v + v.rotate_elements_left::<4>();
+            v.simd_eq(Simd::splat(MAGIC)).to_bitmask() & lines == lines
+        };
 
-        // load the data into the arrays, padding zeros at the end.
-        for i in 0..P::ORDER {
-            for j in rows[i] {
-                buffs[i].push(*j);
-            }
-            for j in cols[i] {
-                buffs[i].push(*j);
-            }
-            buffs[i].push(t1[i]);
-            buffs[i].push(t2[i]);
+        let a = self.to_bytes();
+        let rows = simd_swizzle!(a, [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15]);
+        let diagonals = simd_swizzle!(a, [0, 3, 0, 0, 5, 6, 0, 0, 10, 9, 0, 0, 15, 12, 0, 0]);
 
-            if pad != 0 {
-                for _ in 0..pad {
-                    buffs[i].push(0);
-                }
-            }
-        }
+        (check(a, 0xF) && check(rows, 0xF) && check(diagonals, 0x3)).then_some(*self)
+    }
+}
 
-        // The result vector, containing padding to match the summing arrays.
-        let mut r_vec: Vec<u32> = vec![0; P::CONSTRAINT_VECTORS + pad];
-        for item in r_vec.iter_mut().take(P::CONSTRAINT_VECTORS) {
-            *item = P::MAGIC_SUM;
-        }
+//-------------------------------------------------------------------------------------------------
 
-        // Turn slices from the summing vectors into SIMD arrays and add each of them together.
-        let fv = buffs
-            .into_iter()
-            .fold(Simd::splat(0), |a, n| a + Simd::from_slice(&n));
+/// The number of `u32` lanes `check_n_v` adds a square of order `n` in: as many as fit in the
+/// widest SIMD register of the CPU, but no more than `n` rounded up to a power of two, and at
+/// least 4.
+#[must_use]
+pub(crate) fn vector_lanes(n: usize) -> usize {
+    (simd_width() / 32).min(n.next_power_of_two()).clamp(4, 16)
+}
 
-        // If the SIMD vector is equal to the expected result SIMD vector, then the square must be
-        // magic.
-        let sumv: Simd<u32, B> = Simd::from_slice(r_vec.as_slice());
-        fv.simd_eq(sumv).all().then_some((*self).clone())
+/// Checks that every constraint vector of a square sums to `P::MAGIC_SUM`, in `lanes` lanes.
+#[inline]
+pub(crate) fn sum_constraint_vectors_v<P: Params>(data: &[u32], lanes: usize) -> bool {
+    match lanes {
+        0..=4 => sum_lanes::<P, 4>(data),
+        5..=8 => sum_lanes::<P, 8>(data),
+        _ => sum_lanes::<P, 16>(data),
     }
 }
 
+/// Sums the columns of a square `B` at a time by adding up its rows as vectors, then each row
+/// and both diagonals in place. Lanes past the last column load zeros and are not compared. Sums
+/// wrap as in `check_n_s`.
+fn sum_lanes<P: Params, const B: usize>(data: &[u32]) -> bool {
+    let n = P::ORDER;
+    let magic = Simd::<u32, B>::splat(P::MAGIC_SUM);
+    let rows = data.chunks_exact(n);
+
+    let columns = (0..n).step_by(B).all(|j| {
+        let len = B.min(n - j);
+        let sums = rows.clone().fold(Simd::splat(0), |acc, row| {
+            acc + Simd::load_or_default(&row[j..j + len])
+        });
+        let wanted = u64::MAX >> (64 - len);
+        sums.simd_eq(magic).to_bitmask() & wanted == wanted
+    });
+
+    let diagonal = |start: usize, step: usize| {
+        let sum = (0..n).fold(0u32, |acc, k| acc.wrapping_add(data[start + k * step]));
+        sum == P::MAGIC_SUM
+    };
+
+    columns
+        && rows.clone().all(|row| {
+            let sums = row.chunks(B).fold(Simd::<u32, B>::splat(0), |acc, chunk| {
+                acc + Simd::load_or_default(chunk)
+            });
+            sums.reduce_sum() == P::MAGIC_SUM
+        })
+        && diagonal(0, n + 1)
+        && diagonal(n - 1, n - 1)
+}
+
+macro_rules! impl_generic_vector_checker_for_type {
+    ($t:tt) => {
+        impl<P: Params + Copy> $t<P>
+        where
+            [(); P::ELEMENTS]:,
+        {
+            /// Check if a square-like object of any size is magic.
+            /// Computations are vectorized and implemented using `std::simd`, in lanes chosen by
+            /// the order and the SIMD width of the CPU.
+            #[inline]
+            #[must_use]
+            pub fn check_n_v(&self) -> Option<$t<P>> {
+                sum_constraint_vectors_v::<P>(&self.square.data, vector_lanes(P::ORDER))
+                    .then(|| (*self).clone())
+            }
+        }
+    };
+}
+
+impl_generic_vector_checker_for_type!(Construction);
+impl_generic_vector_checker_for_type!(Permutation);
+
 #[cfg(test)]
 mod test_check {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
     use super::*;
-    use crate::{Enumerable, ParameterSetError};
+    use crate::{
+        Enumerable, LocalSearchSolver, ParameterSetError, O10, O11, O12, O13, O14, O15, O16, O6,
+        O7, O8, O9,
+    };
 
     #[test]
     fn test_safe_3() -> Result<(), ParameterSetError> {
@@ -470,4 +608,134 @@ mod test_check {
 
         Ok(())
     }
+
+    #[test]
+    fn test_order_5() -> Result<(), ParameterSetError> {
+        let c = Construction::<O5>::siamese(2);
+        let a = Permutation::<O5>::try_from(c.square.data.as_slice())?;
+
+        assert_eq!(a.check_v(), Some(a));
+        assert_eq!(unsafe { a.check_v_unsafe() }, Some(a));
+        assert_eq!(c.check_v(), Some(c.clone()));
+        assert_eq!(unsafe { c.check_v_unsafe() }, Some(c.clone()));
+
+        let mut data = c.square.data.clone();
+        data.swap(0, 24);
+        let b = Permutation::<O5>::try_from(data.as_slice())?;
+        assert_eq!(b.check_v(), None);
+        assert_eq!(unsafe { b.check_v_unsafe() }, None);
+
+        Ok(())
+    }
+
+    /// Squares that are magic, or fail on only some families of constraint vectors, from a magic
+    /// square of order `P::ORDER`, together with shuffles and squares of arbitrary entries.
+    fn cases<P: Params + Copy + Send + Sync>(seed: u64) -> Vec<Vec<u32>>
+    where
+        [(); P::ELEMENTS]:,
+    {
+        let n = P::ORDER;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let magic = LocalSearchSolver::<P>::new().seed(seed).solve().unwrap();
+        let mut cases = vec![magic.square.data.clone()];
+
+        for _ in 0..200 {
+            // Swapping two cells.
+            let mut a = magic.square.data.clone();
+            a.swap(rng.gen_range(0..P::ELEMENTS), rng.gen_range(0..P::ELEMENTS));
+
+            // Swapping two rows keeps the rows and columns.
+            let (i, j) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            let mut b = magic.square.data.clone();
+            for k in 0..n {
+                b.swap(i * n + k, j * n + k);
+            }
+
+            // Moving a value around a rectangle keeps the rows and columns.
+            let (k, l) = (rng.gen_range(0..n), rng.gen_range(0..n));
+            let mut c = magic.square.data.clone();
+            c[i * n + k] += 1;
+            c[j * n + l] += 1;
+            c[i * n + l] -= 1;
+            c[j * n + k] -= 1;
+
+            let mut d = Construction::<P>::identity();
+            d.shuffle_with(&mut rng);
+
+            let top = 2 * P::ELEMENTS as u32;
+            let e = (0..P::ELEMENTS).map(|_| rng.gen_range(0..=top)).collect();
+
+            cases.extend([a, b, c, d.square.data, e]);
+        }
+
+        cases
+    }
+
+    fn differential<P: Params + Copy + Send + Sync>(seed: u64)
+    where
+        [(); P::ELEMENTS]:,
+        [(); P::ORDER]:,
+    {
+        for data in cases::<P>(seed) {
+            let a = Construction::<P> {
+                square: VecSquare::from_vec(data),
+            };
+            let expected = a.check_n_s().is_some();
+            assert_eq!(a.check_n_v().is_some(), expected, "{:?}", a.square.data);
+
+            let b = Permutation::<P>::try_from(a.square.data.as_slice()).unwrap();
+            assert_eq!(b.check_n_v().is_some(), expected, "{:?}", b.square.data);
+        }
+    }
+
+    #[test]
+    fn test_differential() {
+        differential::<O3>(3);
+        differential::<O4>(4);
+        differential::<O5>(5);
+        differential::<O6>(6);
+        differential::<O7>(7);
+        differential::<O8>(8);
+        differential::<O9>(9);
+        differential::<O10>(10);
+        differential::<O11>(11);
+        differential::<O12>(12);
+        differential::<O13>(13);
+        differential::<O14>(14);
+        differential::<O15>(15);
+        differential::<O16>(16);
+    }
+
+    #[test]
+    fn test_differential_5() {
+        for data in cases::<O5>(25) {
+            let a = Permutation::<O5>::try_from(data.as_slice()).unwrap();
+            let b = Construction::<O5> {
+                square: VecSquare::from_vec(data),
+            };
+
+            assert_eq!(a.check_v(), a.check_n_s());
+            assert_eq!(unsafe { a.check_v_unsafe() }, a.check_n_s());
+            assert_eq!(b.check_v(), b.check_n_s());
+        }
+    }
+
+    #[test]
+    fn test_packed() -> Result<(), ParameterSetError> {
+        let mut rng = StdRng::seed_from_u64(16);
+        let magic = Permutation::<O4>::kth(80_867_885_530);
+
+        for a in magic.generate_d() {
+            let mut b = a;
+            b.square.swap(rng.gen_range(0..16), rng.gen_range(0..16));
+            let c = Permutation::<O4>::kth(rng.gen_range(0..20_922_789_888_000));
+
+            for p in [a, b, c] {
+                let packed = PackedPerm16::try_from(p)?;
+                assert_eq!(packed.check_v().is_some(), p.check_n_s().is_some());
+            }
+        }
+
+        Ok(())
+    }
 }
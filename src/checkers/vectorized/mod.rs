@@ -1,3 +1,5 @@
+mod batch;
 mod check;
 
+pub use batch::*;
 pub use check::*;
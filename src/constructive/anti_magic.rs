@@ -0,0 +1,529 @@
+use std::marker::PhantomData;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{constraint_lines, Construction, Params, SquareCount, VecSquare};
+
+/// The condition on the sums of the rows, columns and both diagonals of a square that a search
+/// looks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LineSums {
+    /// The sums are all distinct, as in a heterosquare.
+    Distinct,
+    /// The sums form a run of consecutive numbers, as in an anti-magic square.
+    Consecutive,
+}
+
+impl LineSums {
+    /// Measures how far sorted line sums are from meeting the condition, which they meet exactly
+    /// when it is zero: the number of repeated sums, plus for `Consecutive` how much wider than
+    /// a run the sums spread.
+    fn cost(self, sorted: &[u32]) -> u32 {
+        let repeats = sorted.windows(2).filter(|w| w[0] == w[1]).count() as u32;
+        match self {
+            LineSums::Distinct => repeats,
+            LineSums::Consecutive => {
+                let spread = sorted[sorted.len() - 1] - sorted[0];
+                repeats + spread.saturating_sub(sorted.len() as u32 - 1)
+            }
+        }
+    }
+}
+
+/// Returns the heterosquares of order `P::ORDER`, one for each class of squares related by
+/// rotation and reflection, stopping after `limit` squares if one is given.
+///
+/// The search is exhaustive, which is quick for order 3 and slow from order 4 on. Use an
+/// `AntiMagicSolver` to find a single square of a larger order.
+///
+/// # Panics
+/// - If `P::ELEMENTS` is greater than 128.
+#[must_use]
+pub fn heterosquares<P: Params + Copy>(limit: Option<usize>) -> Vec<Construction<P>>
+where
+    [(); P::ELEMENTS]:,
+{
+    find::<P>(LineSums::Distinct, limit)
+}
+
+/// Counts the heterosquares of order `P::ORDER`. Order 3 has 3120 classes.
+///
+/// # Panics
+/// - If `P::ELEMENTS` is greater than 128.
+#[must_use]
+pub fn count_heterosquares<P: Params>() -> SquareCount {
+    count::<P>(LineSums::Distinct)
+}
+
+/// Returns the anti-magic squares of order `P::ORDER`, one for each class of squares related by
+/// rotation and reflection, stopping after `limit` squares if one is given.
+///
+/// There are none of order 3 or less. The search is exhaustive, and while the first squares of
+/// order 4 are found at once, there are far too many to list. Use an
+/// `AntiMagicSolver` to find a single square of a larger order.
+///
+/// # Panics
+/// - If `P::ELEMENTS` is greater than 128.
+#[must_use]
+pub fn anti_magic_squares<P: Params + Copy>(limit: Option<usize>) -> Vec<Construction<P>>
+where
+    [(); P::ELEMENTS]:,
+{
+    find::<P>(LineSums::Consecutive, limit)
+}
+
+/// Counts the anti-magic squares of order `P::ORDER`, which is out of reach from order 4 on.
+///
+/// # Panics
+/// - If `P::ELEMENTS` is greater than 128.
+#[must_use]
+pub fn count_anti_magic<P: Params>() -> SquareCount {
+    count::<P>(LineSums::Consecutive)
+}
+
+fn find<P: Params + Copy>(sums: LineSums, limit: Option<usize>) -> Vec<Construction<P>>
+where
+    [(); P::ELEMENTS]:,
+{
+    let mut found = Vec::new();
+    Backtrack::<P>::new(sums).visit(&mut |data| {
+        found.push(Construction {
+            square: VecSquare::from_vec(data.to_vec()),
+        });
+        limit.is_none_or(|l| found.len() < l)
+    });
+
+    found
+}
+
+fn count<P: Params>(sums: LineSums) -> SquareCount {
+    let mut classes = 0;
+    Backtrack::<P>::new(sums).visit(&mut |_| {
+        classes += 1;
+        true
+    });
+
+    // The entries are distinct, so no square is fixed by a rotation or reflection.
+    SquareCount {
+        total: classes * 8,
+        classes,
+    }
+}
+
+/// An exhaustive search filling a square cell by cell, alternating between the rest of a row
+/// and the rest of a column so that lines are completed early.
+///
+/// A line is checked against the lines already complete as soon as its last cell is filled. For
+/// `Consecutive`, every line must also be able to reach a sum within a run's width of the
+/// complete lines using the numbers left, and the rows left, like the columns left, must be able
+/// to take distinct sums in that range making up the sum of every number. Only the square of
+/// each class with its least corner top left, and its top right corner below its bottom left, is
+/// kept.
+struct Backtrack<P: Params> {
+    sums: LineSums,
+    /// The cells in the order they are filled.
+    order: Vec<usize>,
+    lines: Vec<Vec<usize>>,
+    /// The lines through every cell.
+    cell_lines: Vec<Vec<usize>>,
+    data: Vec<u32>,
+    line_sum: Vec<u32>,
+    line_empty: Vec<usize>,
+    /// The sums of the complete lines.
+    complete: Vec<u32>,
+    used: u128,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params> Backtrack<P> {
+    fn new(sums: LineSums) -> Self {
+        assert!(
+            P::ELEMENTS <= 128,
+            "Searches track at most 128 numbers as used!"
+        );
+
+        let n = P::ORDER;
+        let order = (0..n)
+            .flat_map(|k| {
+                let row = (k..n).map(move |j| k * n + j);
+                row.chain((k + 1..n).map(move |i| i * n + k))
+            })
+            .collect();
+
+        let lines = constraint_lines::<P>();
+        let mut cell_lines = vec![Vec::new(); P::ELEMENTS];
+        for (l, line) in lines.iter().enumerate() {
+            for &cell in line {
+                cell_lines[cell].push(l);
+            }
+        }
+
+        Self {
+            sums,
+            order,
+            line_empty: lines.iter().map(Vec::len).collect(),
+            line_sum: vec![0; lines.len()],
+            lines,
+            cell_lines,
+            data: vec![0; P::ELEMENTS],
+            complete: Vec::new(),
+            used: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Calls `visit` with every square found, stopping once it returns `false`.
+    fn visit(mut self, visit: &mut impl FnMut(&[u32]) -> bool) {
+        self.fill(0, visit);
+    }
+
+    fn fill(&mut self, step: usize, visit: &mut impl FnMut(&[u32]) -> bool) -> bool {
+        if step == P::ELEMENTS {
+            return visit(&self.data);
+        }
+
+        let cell = self.order[step];
+        for value in 1..=P::ELEMENTS as u32 {
+            if self.used & 1 << (value - 1) != 0 || !self.canonical(cell, value) {
+                continue;
+            }
+
+            let complete = self.complete.len();
+            if self.place(cell, value) && self.reachable() && !self.fill(step + 1, visit) {
+                return false;
+            }
+            self.complete.truncate(complete);
+            self.remove(cell, value);
+        }
+
+        true
+    }
+
+    /// Whether a value in a corner keeps the square the one kept from its class. The top left
+    /// corner is filled first, then the top right, the bottom left and the bottom right.
+    fn canonical(&self, cell: usize, value: u32) -> bool {
+        let n = P::ORDER;
+        let (top_right, bottom_left) = (n - 1, n * (n - 1));
+        if (cell == top_right || cell == bottom_left || cell == P::ELEMENTS - 1)
+            && value < self.data[0]
+        {
+            return false;
+        }
+
+        cell != bottom_left || value > self.data[top_right]
+    }
+
+    /// Fills a cell, returning whether the lines it completes keep their sums valid.
+    fn place(&mut self, cell: usize, value: u32) -> bool {
+        self.used |= 1 << (value - 1);
+        self.data[cell] = value;
+
+        let mut valid = true;
+        for &l in &self.cell_lines[cell] {
+            self.line_sum[l] += value;
+            self.line_empty[l] -= 1;
+            if self.line_empty[l] == 0 {
+                valid &= !self.complete.contains(&self.line_sum[l]);
+                self.complete.push(self.line_sum[l]);
+            }
+        }
+
+        valid
+    }
+
+    fn remove(&mut self, cell: usize, value: u32) {
+        self.used &= !(1 << (value - 1));
+        self.data[cell] = 0;
+
+        for &l in &self.cell_lines[cell] {
+            self.line_sum[l] -= value;
+            self.line_empty[l] += 1;
+        }
+    }
+
+    /// Whether the lines left can still take sums making a run with the complete lines.
+    #[allow(clippy::cast_possible_truncation)]
+    fn reachable(&self) -> bool {
+        if self.sums == LineSums::Distinct {
+            return true;
+        }
+        let (Some(&lo), Some(&hi)) = (self.complete.iter().min(), self.complete.iter().max())
+        else {
+            return true;
+        };
+
+        let width = self.lines.len() as u32;
+        if hi - lo >= width {
+            return false;
+        }
+        let (floor, ceiling) = ((hi + 1).saturating_sub(width), lo + width - 1);
+
+        let left = (1..=P::ELEMENTS as u32).filter(|v| self.used & 1 << (v - 1) == 0);
+        let prefix_sums = |values: &mut dyn Iterator<Item = u32>| {
+            values
+                .scan(0, |s, v| {
+                    *s += v;
+                    Some(*s)
+                })
+                .collect::<Vec<u32>>()
+        };
+        let least = prefix_sums(&mut left.clone());
+        let greatest = prefix_sums(&mut left.rev());
+
+        let lines_reachable = (0..self.lines.len()).all(|l| {
+            let (sum, empty) = (self.line_sum[l], self.line_empty[l]);
+            empty == 0 || sum + least[empty - 1] <= ceiling && sum + greatest[empty - 1] >= floor
+        });
+        if !lines_reachable {
+            return false;
+        }
+
+        // Both the rows and the columns sum to the sum of every number.
+        let n = P::ORDER;
+        let total = (P::ELEMENTS * (P::ELEMENTS + 1) / 2) as u32;
+        let free = (floor..=ceiling).filter(|s| !self.complete.contains(s));
+        let least = prefix_sums(&mut free.clone());
+        let greatest = prefix_sums(&mut free.rev());
+
+        [0..n, n..2 * n].into_iter().all(|family| {
+            let (done, left): (Vec<usize>, Vec<usize>) =
+                family.partition(|&l| self.line_empty[l] == 0);
+            let needed = total - done.iter().map(|&l| self.line_sum[l]).sum::<u32>();
+
+            let m = left.len();
+            m == 0 || m <= least.len() && least[m - 1] <= needed && needed <= greatest[m - 1]
+        })
+    }
+}
+
+/// A heuristic solver that searches for anti-magic squares or heterosquares of any order by
+/// simulated annealing over cell swaps.
+///
+/// Each run starts from a shuffled square, and its cost is the `LineSums` measure of how far
+/// the sums of its lines are from meeting the condition. Random swaps of two cells are proposed
+/// and worsening ones accepted with the Metropolis probability. A run that has not reached zero
+/// cost after `iterations` proposals is restarted from a fresh shuffle, up to `restarts` times.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{AntiMagicSolver, O6};
+///
+/// let a = AntiMagicSolver::<O6>::new().seed(6).solve().unwrap();
+/// assert!(a.check_anti_magic_n_s().is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct AntiMagicSolver<P: Params> {
+    sums: LineSums,
+    seed: Option<u64>,
+    restarts: usize,
+    iterations: u64,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params> Default for AntiMagicSolver<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Params> AntiMagicSolver<P> {
+    /// Creates a new solver for anti-magic squares with an entropy seed.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            sums: LineSums::Consecutive,
+            seed: None,
+            restarts: 100,
+            iterations: 10_000 * P::ELEMENTS as u64,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Searches for heterosquares instead.
+    #[must_use]
+    pub fn heterosquare(mut self) -> Self {
+        self.sums = LineSums::Distinct;
+        self
+    }
+
+    /// Seeds the random number generator.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the number of times the search is restarted from a fresh shuffle.
+    #[must_use]
+    pub fn restarts(mut self, restarts: usize) -> Self {
+        self.restarts = restarts;
+        self
+    }
+
+    /// Sets the number of proposals per run.
+    #[must_use]
+    pub fn iterations(mut self, iterations: u64) -> Self {
+        self.iterations = iterations;
+        self
+    }
+}
+
+impl<P: Params + Copy> AntiMagicSolver<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Runs the solver, returning the first square found, or `None` if every run failed.
+    #[must_use]
+    pub fn solve(&self) -> Option<Construction<P>> {
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        for _ in 0..=self.restarts {
+            let mut start = Construction::<P>::identity();
+            start.shuffle_with(&mut rng);
+
+            let mut run = Anneal::<P>::new(self.sums, start.square.data);
+            if run.search(self.iterations, &mut rng) {
+                return Some(Construction {
+                    square: VecSquare::from_vec(run.data),
+                });
+            }
+        }
+
+        None
+    }
+}
+
+/// State of a single annealing run, with the running sums of every line.
+struct Anneal<P: Params> {
+    sums: LineSums,
+    data: Vec<u32>,
+    cell_lines: Vec<Vec<usize>>,
+    line_sum: Vec<u32>,
+    cost: u32,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params> Anneal<P> {
+    fn new(sums: LineSums, data: Vec<u32>) -> Self {
+        let lines = constraint_lines::<P>();
+        let mut cell_lines = vec![Vec::new(); P::ELEMENTS];
+        for (l, line) in lines.iter().enumerate() {
+            for &cell in line {
+                cell_lines[cell].push(l);
+            }
+        }
+
+        let line_sum = lines
+            .iter()
+            .map(|line| line.iter().map(|&c| data[c]).sum())
+            .collect();
+        let mut run = Self {
+            sums,
+            data,
+            cell_lines,
+            line_sum,
+            cost: 0,
+            phantom: PhantomData,
+        };
+        run.cost = run.measure();
+        run
+    }
+
+    fn measure(&self) -> u32 {
+        let mut sorted = self.line_sum.clone();
+        sorted.sort_unstable();
+        self.sums.cost(&sorted)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        let (va, vb) = (self.data[a], self.data[b]);
+        for &l in &self.cell_lines[a] {
+            self.line_sum[l] = self.line_sum[l] - va + vb;
+        }
+        for &l in &self.cell_lines[b] {
+            self.line_sum[l] = self.line_sum[l] - vb + va;
+        }
+        self.data.swap(a, b);
+    }
+
+    /// Anneals for up to `iterations` proposals, returning whether the cost reached zero.
+    #[allow(clippy::cast_precision_loss)]
+    fn search(&mut self, iterations: u64, rng: &mut impl Rng) -> bool {
+        let mut temperature = 1.0;
+        let cooling = (0.01f64).powf(1.0 / iterations as f64);
+
+        for _ in 0..iterations {
+            if self.cost == 0 {
+                return true;
+            }
+
+            let a = rng.gen_range(0..P::ELEMENTS);
+            let b = rng.gen_range(0..P::ELEMENTS);
+            if a == b {
+                continue;
+            }
+
+            self.swap(a, b);
+            let cost = self.measure();
+            let delta = f64::from(cost) - f64::from(self.cost);
+            if delta <= 0.0 || rng.gen::<f64>() < (-delta / temperature).exp() {
+                self.cost = cost;
+            } else {
+                self.swap(a, b);
+            }
+            temperature *= cooling;
+        }
+
+        self.cost == 0
+    }
+}
+
+#[cfg(test)]
+mod test_anti_magic {
+    use crate::{O3, O4, O6, O8};
+
+    use super::*;
+
+    #[test]
+    fn test_order_3() {
+        assert_eq!(
+            count_heterosquares::<O3>(),
+            SquareCount {
+                total: 24960,
+                classes: 3120
+            }
+        );
+        assert!(heterosquares::<O3>(Some(10))
+            .iter()
+            .all(|a| a.check_hetero_n_s().is_some()));
+        assert_eq!(count_anti_magic::<O3>().total, 0);
+    }
+
+    #[test]
+    fn test_order_4() {
+        let squares = anti_magic_squares::<O4>(Some(20));
+
+        assert_eq!(squares.len(), 20);
+        assert!(squares.iter().all(|a| a.check_anti_magic_n_s().is_some()));
+    }
+
+    #[test]
+    fn test_solver() {
+        let a = AntiMagicSolver::<O6>::new().seed(6).solve().unwrap();
+        let b = AntiMagicSolver::<O8>::new().seed(8).solve().unwrap();
+        let c = AntiMagicSolver::<O8>::new().heterosquare().seed(8).solve();
+
+        assert!(a.check_anti_magic_n_s().is_some());
+        assert!(b.check_anti_magic_n_s().is_some());
+        assert!(c.unwrap().check_hetero_n_s().is_some());
+    }
+
+    #[test]
+    fn test_cost() {
+        assert_eq!(LineSums::Distinct.cost(&[6, 12, 12, 15, 15, 15]), 3);
+        assert_eq!(LineSums::Consecutive.cost(&[1, 2, 3, 4]), 0);
+        assert_eq!(LineSums::Consecutive.cost(&[1, 2, 2, 6]), 3);
+    }
+}
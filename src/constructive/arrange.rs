@@ -0,0 +1,206 @@
+use std::marker::PhantomData;
+
+use crate::{
+    magic_sum_of, CompletionStats, FigureSolver, MagicFigure, NonNormalSquare, ParameterSetError,
+    Params,
+};
+
+/// A solver arranging a given multiset of integers into magic squares.
+///
+/// The magic constant is the only one the entries admit, their total divided by the order. The
+/// search is a `FigureSolver` on the figure of the square's constraint vectors, as in
+/// `CompletionSolver`.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{ArrangementSolver, O3};
+///
+/// let primes = [5, 17, 29, 47, 59, 71, 89, 101, 113];
+/// let mut solver = ArrangementSolver::<O3>::new(&primes).unwrap();
+///
+/// assert_eq!(solver.count(None), 8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArrangementSolver<P: Params> {
+    solver: FigureSolver,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params + Copy> ArrangementSolver<P>
+where
+    [(); P::ORDER]:,
+{
+    /// Creates a new solver for a multiset of entries.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match `P::ELEMENTS`.
+    /// - If the total of the entries is not divisible by `P::ORDER`.
+    pub fn new(entries: &[i64]) -> Result<Self, ParameterSetError> {
+        if entries.len() != P::ELEMENTS {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match P::ELEMENTS!".to_string(),
+            ));
+        }
+        let magic_sum = magic_sum_of::<P>(entries)?;
+
+        Ok(Self {
+            solver: FigureSolver::new(&MagicFigure::square::<P>(), entries, magic_sum)?,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Fixes the entry of a cell, so that only arrangements with `value` at `cell` are found.
+    ///
+    /// # Errors
+    /// - If the cell is out of range or already fixed.
+    /// - If no unused entry equals `value`.
+    pub fn fix(self, cell: usize, value: i64) -> Result<Self, ParameterSetError> {
+        Ok(Self {
+            solver: self.solver.fix(cell, value)?,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns the magic constant of every arrangement.
+    #[must_use]
+    pub fn magic_sum(&self) -> i64 {
+        self.solver.magic_sum()
+    }
+
+    /// Returns the search statistics gathered so far.
+    #[must_use]
+    pub fn stats(&self) -> CompletionStats {
+        self.solver.stats
+    }
+
+    /// Finds one magic arrangement, if any exists.
+    pub fn solve(&mut self) -> Option<NonNormalSquare<P>> {
+        let mut found = None;
+        self.search(&mut |s| {
+            found = Some(s);
+            false
+        });
+        found
+    }
+
+    /// Finds every magic arrangement.
+    pub fn solve_all(&mut self) -> Vec<NonNormalSquare<P>> {
+        let mut found = vec![];
+        self.search(&mut |s| {
+            found.push(s);
+            true
+        });
+        found
+    }
+
+    /// Counts the magic arrangements, stopping early once `limit` arrangements have been found.
+    pub fn count(&mut self, limit: Option<u64>) -> u64 {
+        self.solver.count(limit)
+    }
+
+    /// Runs the search from the fixed cells, calling `on_solution` with each arrangement.
+    /// The search stops as soon as `on_solution` returns `false`.
+    fn search(&mut self, on_solution: &mut dyn FnMut(NonNormalSquare<P>) -> bool) {
+        let magic_sum = self.solver.magic_sum();
+        self.solver
+            .search(&mut |data| match NonNormalSquare::new(data, magic_sum)
+                .ok()
+                .and_then(|s| s.check_n_s())
+            {
+                Some(square) => on_solution(square),
+                None => true,
+            });
+    }
+}
+
+impl<P: Params + Copy> NonNormalSquare<P>
+where
+    [(); P::ORDER]:,
+{
+    /// Arranges a multiset of entries into a magic square, if any arrangement exists.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match `P::ELEMENTS`.
+    /// - If the total of the entries is not divisible by `P::ORDER`.
+    pub fn arrange(entries: &[i64]) -> Result<Option<Self>, ParameterSetError> {
+        Ok(ArrangementSolver::new(entries)?.solve())
+    }
+
+    /// Returns every magic arrangement of a multiset of entries.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match `P::ELEMENTS`.
+    /// - If the total of the entries is not divisible by `P::ORDER`.
+    pub fn arrangements(entries: &[i64]) -> Result<Vec<Self>, ParameterSetError> {
+        Ok(ArrangementSolver::new(entries)?.solve_all())
+    }
+}
+
+#[cfg(test)]
+mod test_arrange {
+    use crate::{O3, O4};
+
+    use super::*;
+
+    #[test]
+    fn test_normal_3() -> Result<(), ParameterSetError> {
+        let squares = NonNormalSquare::<O3>::arrangements(&(1..=9).collect::<Vec<_>>())?;
+
+        assert_eq!(squares.len(), 8);
+        assert!(squares.iter().all(NonNormalSquare::is_normal));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_consecutive_4() -> Result<(), ParameterSetError> {
+        let entries = (-7..=8).collect::<Vec<_>>();
+        let mut solver = ArrangementSolver::<O4>::new(&entries)?;
+
+        assert_eq!(solver.magic_sum(), 2);
+        assert_eq!(solver.count(None), 7040);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_durer() -> Result<(), ParameterSetError> {
+        // Dürer's Melencolia I square, with the date 1514 in the bottom row.
+        let entries = (1..=16).collect::<Vec<_>>();
+        let solver = ArrangementSolver::<O4>::new(&entries)?
+            .fix(0, 16)?
+            .fix(13, 15)?
+            .fix(14, 14)?
+            .fix(15, 1)?;
+        let squares = solver.clone().solve_all();
+
+        let durer = vec![16, 3, 2, 13, 5, 10, 11, 8, 9, 6, 7, 12, 4, 15, 14, 1];
+        assert!(squares.iter().any(|s| s.data == durer));
+        assert!(squares.iter().all(|s| s.data[13..] == [15, 14, 1]));
+
+        assert!(solver.clone().fix(13, 2).is_err());
+        assert!(solver.fix(0, 2).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeated() -> Result<(), ParameterSetError> {
+        let mut solver = ArrangementSolver::<O3>::new(&[4; 9])?;
+        assert_eq!(solver.count(None), 1);
+
+        let entries = [1, 1, 1, 2, 2, 2, 3, 3, 3];
+        let squares = NonNormalSquare::<O3>::arrangements(&entries)?;
+        assert!(!squares.is_empty());
+        assert!(squares
+            .iter()
+            .all(|s| s.check_entries_n_s(&entries).is_some()));
+
+        assert!(NonNormalSquare::<O3>::arrange(&[1, 2, 3, 4, 5, 6, 7, 8, 12])?.is_none());
+        assert!(NonNormalSquare::<O3>::arrange(&[1, 2, 3, 4, 5, 6, 7, 8, 11]).is_err());
+        assert!(NonNormalSquare::<O3>::arrange(&[1, 2, 3]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,228 @@
+use std::marker::PhantomData;
+
+use crate::{Construction, FigureSolver, MagicFigure, ParameterSetError, Params, VecSquare};
+
+/// Counters describing how much work a `CompletionSolver` has done.
+///
+/// `propagations` counts cells filled because a constraint vector had a single empty cell left,
+/// `branches` counts values tried at cells where no constraint forced a value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompletionStats {
+    pub propagations: u64,
+    pub branches: u64,
+    pub backtracks: u64,
+}
+
+/// A solver for completing a partially filled `Construction<P>` into magic squares.
+///
+/// Empty cells hold `0`, as in `Construction::zeros`. The search is a `FigureSolver` arranging
+/// `1..=P::ELEMENTS` on the figure of the square's constraint vectors, with the clues fixed
+/// before the search starts.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{CompletionSolver, Construction, O4};
+///
+/// let mut c = Construction::<O4>::zeros();
+/// c.square.data[0] = 1;
+/// c.square.data[5] = 16;
+///
+/// assert_eq!(CompletionSolver::new(&c).unwrap().count(None), 24);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CompletionSolver<P: Params> {
+    solver: FigureSolver,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params> CompletionSolver<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Creates a new solver from a partially filled construction.
+    ///
+    /// # Errors
+    /// - If the construction does not have `P::ELEMENTS` cells.
+    /// - If a clue lies outside `1..=P::ELEMENTS` or appears more than once.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn new(partial: &Construction<P>) -> Result<Self, ParameterSetError> {
+        if partial.square.data.len() != P::ELEMENTS {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match P::ELEMENTS!".to_string(),
+            ));
+        }
+
+        let entries = (1..=P::ELEMENTS as i64).collect::<Vec<_>>();
+        let mut solver = FigureSolver::new(
+            &MagicFigure::square::<P>(),
+            &entries,
+            i64::from(P::MAGIC_SUM),
+        )?;
+        for (cell, &value) in partial.square.data.iter().enumerate() {
+            if value != 0 {
+                solver = solver.fix(cell, i64::from(value)).map_err(|_| {
+                    ParameterSetError::InvalidElements(
+                        "Clues must be distinct values in 1..=P::ELEMENTS!".to_string(),
+                    )
+                })?;
+            }
+        }
+
+        Ok(Self {
+            solver,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns the search statistics gathered so far.
+    #[must_use]
+    pub fn stats(&self) -> CompletionStats {
+        self.solver.stats
+    }
+
+    /// Finds one magic completion, if any exists.
+    pub fn solve(&mut self) -> Option<Construction<P>> {
+        self.solver.solve().map(Self::construction)
+    }
+
+    /// Finds every magic completion.
+    pub fn solve_all(&mut self) -> Vec<Construction<P>> {
+        self.solver
+            .solve_all()
+            .into_iter()
+            .map(Self::construction)
+            .collect()
+    }
+
+    /// Counts the magic completions, stopping early once `limit` completions have been found.
+    pub fn count(&mut self, limit: Option<u64>) -> u64 {
+        self.solver.count(limit)
+    }
+
+    /// Converts an arrangement of `1..=P::ELEMENTS` into a construction.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn construction(data: Vec<i64>) -> Construction<P> {
+        Construction {
+            square: VecSquare::from_vec(data.into_iter().map(|x| x as u32).collect()),
+        }
+    }
+}
+
+/// Builds the index sets of the rows, columns and both diagonals of a square.
+pub(crate) fn constraint_lines<P: Params>() -> Vec<Vec<usize>> {
+    let n = P::ORDER;
+    let mut lines = Vec::with_capacity(P::CONSTRAINT_VECTORS);
+    lines.extend((0..n).map(|r| (0..n).map(|c| r * n + c).collect()));
+    lines.extend((0..n).map(|c| (0..n).map(|r| r * n + c).collect()));
+    lines.push((0..n).map(|a| a * (n + 1)).collect());
+    lines.push((0..n).map(|a| (a + 1) * (n - 1)).collect());
+    lines
+}
+
+impl<P: Params + Copy> Construction<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Completes a partially filled construction, where empty cells hold `0`, into a magic
+    /// square. Returns `None` if the clues are invalid or admit no magic completion.
+    #[must_use]
+    pub fn complete(&self) -> Option<Self> {
+        CompletionSolver::new(self).ok()?.solve()
+    }
+
+    /// Returns every magic completion of a partially filled construction.
+    #[must_use]
+    pub fn completions(&self) -> Vec<Self> {
+        CompletionSolver::new(self).map_or_else(|_| vec![], |mut s| s.solve_all())
+    }
+
+    /// Counts the magic completions of a partially filled construction.
+    #[must_use]
+    pub fn count_completions(&self) -> u64 {
+        CompletionSolver::new(self).map_or(0, |mut s| s.count(None))
+    }
+}
+
+#[cfg(test)]
+mod test_completion {
+    use crate::{CheckScalar, Enumerable, Permutation, O3, O4, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_complete_3() {
+        let a = Construction::<O3>::zeros();
+        let solutions = a.completions();
+
+        assert_eq!(solutions.len(), 8);
+        assert!(solutions.iter().all(|s| s.check_s().is_some()));
+    }
+
+    #[test]
+    fn test_corner_centre_4() {
+        let mut a = Construction::<O4>::zeros();
+        a.square.data[0] = 1;
+        a.square.data[5] = 16;
+
+        let solutions = a.completions();
+        assert_eq!(solutions.len(), 24);
+        assert!(solutions.iter().all(|s| s.check_s().is_some()));
+        assert!(solutions
+            .iter()
+            .all(|s| s.square.data[0] == 1 && s.square.data[5] == 16));
+
+        a.square.data[5] = 0;
+        assert_eq!(a.count_completions(), 416);
+    }
+
+    #[test]
+    fn test_complete_known_4() -> Result<(), ParameterSetError> {
+        let full = Construction::try_from(Permutation::<O4>::kth(80867885530))?;
+        let mut a = full.clone();
+        for i in [1, 2, 6, 7, 9, 10, 12, 15] {
+            a.square.data[i] = 0;
+        }
+
+        assert_eq!(a.complete(), Some(full.clone()));
+        assert_eq!(full.count_completions(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_complete_5() {
+        let full = Construction::<O5>::siamese(2);
+        let mut a = full.clone();
+        for i in (0..O5::ELEMENTS).step_by(2) {
+            a.square.data[i] = 0;
+        }
+
+        let solved = a.complete().unwrap();
+        assert!(solved.check_n_s().is_some());
+    }
+
+    #[test]
+    fn test_invalid_clues() {
+        let mut a = Construction::<O3>::zeros();
+        a.square.data[0] = 1;
+        a.square.data[1] = 1;
+        assert!(CompletionSolver::new(&a).is_err());
+
+        a.square.data[1] = 10;
+        assert!(CompletionSolver::new(&a).is_err());
+
+        let mut b = Construction::<O3>::zeros();
+        b.square.data[4] = 1;
+        assert_eq!(b.complete(), None);
+    }
+
+    #[test]
+    fn test_limit() {
+        let a = Construction::<O4>::zeros();
+        let mut solver = CompletionSolver::new(&a).unwrap();
+
+        assert_eq!(solver.count(Some(2)), 2);
+        assert!(solver.stats().branches > 0);
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::{magic_series_partitions, Params};
+
+/// The number of squares of some kind, in total and up to symmetry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SquareCount {
+    /// Every square, counting rotations, reflections and other symmetries apart.
+    pub total: u64,
+    /// The essentially different squares, one for each class of squares related by symmetry.
+    pub classes: u64,
+}
+
+/// Counts the semi-magic squares of order `P::ORDER`, whose rows and columns sum to
+/// `P::MAGIC_SUM`, without listing them.
+///
+/// Permuting the rows, permuting the columns and transposing keep a square semi-magic, and no
+/// square is fixed by any of them, so the squares fall into classes of `2 * (P::ORDER!)²`. The
+/// totals match OEIS A271103. Orders up to 4 take seconds, while order 5 is out of reach.
+///
+/// # Panics
+/// - If `P::ELEMENTS` is greater than 128.
+#[must_use]
+pub fn count_semi_magic<P: Params>() -> SquareCount {
+    let total = count_by_rows::<P>(false);
+    let orders = (1..=P::ORDER as u64).product::<u64>();
+
+    SquareCount {
+        total,
+        classes: total / (2 * orders * orders),
+    }
+}
+
+/// Counts the magic squares of order `P::ORDER` without listing them.
+///
+/// Rotations and reflections keep a square magic, and no square is fixed by any of them, so the
+/// squares fall into classes of 8. The classes match OEIS A006052, and orders up to 4 take
+/// seconds.
+///
+/// # Panics
+/// - If `P::ELEMENTS` is greater than 128.
+#[must_use]
+pub fn count_magic<P: Params>() -> SquareCount {
+    let total = count_by_rows::<P>(true);
+
+    SquareCount {
+        total,
+        classes: total / 8,
+    }
+}
+
+/// Counts the squares whose rows are a partition into magic series and whose columns, and
+/// diagonals if asked, sum to `P::MAGIC_SUM`.
+///
+/// The rows are split into a top and a bottom half. The sums of the columns and diagonals over
+/// every arrangement of the top half are tallied, then every arrangement of the bottom half is
+/// matched with the tallies it completes. Without diagonals, reordering the rows or the columns
+/// keeps a square counted, so the series go down the rows in order with the first row sorted,
+/// and the count is multiplied back by `(P::ORDER!)²`.
+fn count_by_rows<P: Params>(diagonals: bool) -> u64 {
+    let n = P::ORDER;
+    let mut total = 0;
+
+    for partition in magic_series_partitions::<P>(None) {
+        let arrangements = partition
+            .iter()
+            .map(|series| series.iter().copied().permutations(n).collect_vec())
+            .collect_vec();
+        // Reflecting top to bottom swaps the halves, so for even orders only the splits with the
+        // first series on top are counted, twice.
+        let tops = if diagonals {
+            let halves = (0..n).combinations(n / 2);
+            halves.filter(|top| n % 2 != 0 || top[0] == 0).collect_vec()
+        } else {
+            vec![(0..n / 2).collect_vec()]
+        };
+
+        for top in tops {
+            let bottom = (0..n).filter(|i| !top.contains(i)).collect_vec();
+            let mut sums = vec![0; if diagonals { n + 2 } else { n }];
+            let ordered = !diagonals;
+
+            let mut tallies = HashMap::<Vec<u32>, u64>::new();
+            let mut tally = |sums: &[u32]| match tallies.get_mut(sums) {
+                Some(count) => *count += 1,
+                None => _ = tallies.insert(sums.to_vec(), 1),
+            };
+            let top = Rows::new(&arrangements, top, 0, ordered);
+            top.place(&mut sums, &mut tally);
+
+            let bottom = Rows::new(&arrangements, bottom, n / 2, ordered);
+            bottom.place(&mut sums, &mut |sums| {
+                let needed = sums.iter().map(|&s| P::MAGIC_SUM.checked_sub(s));
+                if let Some(needed) = needed.collect::<Option<Vec<_>>>() {
+                    total += tallies.get(&needed).copied().unwrap_or(0);
+                }
+            });
+        }
+    }
+
+    if diagonals {
+        total * if n % 2 == 0 { 2 } else { 1 }
+    } else {
+        let orders = (1..=n as u64).product::<u64>();
+        total * orders * orders
+    }
+}
+
+/// Some of the series of a partition, to be arranged into consecutive rows.
+struct Rows<'a> {
+    /// Every order of the entries of every series.
+    arrangements: &'a [Vec<Vec<u32>>],
+    left: Vec<usize>,
+    row: usize,
+    /// Whether the series go down the rows in order, with the first row of the square sorted.
+    ordered: bool,
+}
+
+impl<'a> Rows<'a> {
+    fn new(arrangements: &'a [Vec<Vec<u32>>], left: Vec<usize>, row: usize, ordered: bool) -> Self {
+        Self {
+            arrangements,
+            left,
+            row,
+            ordered,
+        }
+    }
+
+    /// Calls `visit` with the sums of the columns, then of both diagonals if `sums` has room,
+    /// added to `sums` by every arrangement of the rows.
+    fn place(mut self, sums: &mut [u32], visit: &mut impl FnMut(&[u32])) {
+        self.place_from(sums, visit);
+    }
+
+    fn place_from(&mut self, sums: &mut [u32], visit: &mut impl FnMut(&[u32])) {
+        if self.left.is_empty() {
+            visit(sums);
+            return;
+        }
+
+        let n = self.arrangements[0][0].len();
+        let choices = if self.ordered { 1 } else { self.left.len() };
+        for k in 0..choices {
+            let series = self.left.remove(k);
+            let orders = if self.ordered && self.row == 0 {
+                1
+            } else {
+                usize::MAX
+            };
+            for entries in self.arrangements[series].iter().take(orders) {
+                let diagonal =
+                    (sums.len() > n).then(|| [entries[self.row], entries[n - 1 - self.row]]);
+                let cells = entries.iter().chain(diagonal.iter().flatten());
+
+                sums.iter_mut()
+                    .zip(cells.clone())
+                    .for_each(|(s, e)| *s += e);
+                self.row += 1;
+                self.place_from(sums, visit);
+                self.row -= 1;
+                sums.iter_mut().zip(cells).for_each(|(s, e)| *s -= e);
+            }
+            self.left.insert(k, series);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_count {
+    use crate::{O3, O4};
+
+    use super::*;
+
+    #[test]
+    fn test_order_3() {
+        assert_eq!(
+            count_semi_magic::<O3>(),
+            SquareCount {
+                total: 72,
+                classes: 1
+            }
+        );
+        assert_eq!(
+            count_magic::<O3>(),
+            SquareCount {
+                total: 8,
+                classes: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_order_4() {
+        assert_eq!(
+            count_semi_magic::<O4>(),
+            SquareCount {
+                total: 549_504,
+                classes: 477
+            }
+        );
+
+        let magic = count_magic::<O4>();
+        assert_eq!(magic.classes, 880);
+
+        let census = include_str!("../../examples/collected/orderfour/Census.txt");
+        let indexes = census
+            .split(',')
+            .filter(|s| s.chars().any(|c| c.is_ascii_digit()));
+        assert_eq!(magic.total, indexes.count() as u64);
+    }
+}
@@ -0,0 +1,469 @@
+use crate::{CompletionStats, MagicFigure, ParameterSetError};
+
+/// The condition every line of a `FigureSolver` arrangement must meet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineConstraint {
+    /// Every line sums to the given value.
+    Sum(i64),
+    /// Every line multiplies to the given value. All entries must be positive.
+    Product(i64),
+}
+
+impl LineConstraint {
+    /// Returns the sum or product every line shares.
+    #[must_use]
+    pub fn target(self) -> i64 {
+        match self {
+            Self::Sum(t) | Self::Product(t) => t,
+        }
+    }
+
+    /// Returns the aggregate of an empty line.
+    fn identity(self) -> i64 {
+        match self {
+            Self::Sum(_) => 0,
+            Self::Product(_) => 1,
+        }
+    }
+
+    /// Adds an entry to the aggregate of a line.
+    fn combine(self, acc: i64, x: i64) -> i64 {
+        match self {
+            Self::Sum(_) => acc + x,
+            Self::Product(_) => acc.saturating_mul(x),
+        }
+    }
+
+    /// Removes an entry from the aggregate of a line.
+    fn remove(self, acc: i64, x: i64) -> i64 {
+        match self {
+            Self::Sum(_) => acc - x,
+            Self::Product(_) => acc / x,
+        }
+    }
+
+    /// Returns what the empty cells of a line must still contribute.
+    fn remainder(self, acc: i64) -> i64 {
+        match self {
+            Self::Sum(t) => t - acc,
+            Self::Product(t) => t / acc,
+        }
+    }
+
+    /// Returns `true` if `x` may still be placed on a line with aggregate `acc`. A product line
+    /// only admits divisors of its remainder, which keeps every line product a divisor of the
+    /// target.
+    fn admits(self, acc: i64, x: i64) -> bool {
+        match self {
+            Self::Sum(_) => true,
+            Self::Product(_) => self.remainder(acc) % x == 0,
+        }
+    }
+}
+
+/// A solver arranging a given multiset of integers on the cells of a `MagicFigure` so that
+/// every line shares a given sum, or a given product.
+///
+/// The search fills one cell at a time, choosing a cell on the line with the fewest empty cells.
+/// It forces the last cell of a line, and rejects a line with `k` empty cells whose remainder
+/// lies outside the aggregates of the `k` smallest and `k` largest unused entries. Repeated
+/// entries are branched on once, so arrangements differing only by swapping equal entries are
+/// found once.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{FigureSolver, MagicFigure};
+///
+/// let star = MagicFigure::hexagram();
+/// let entries = (1..=12).collect::<Vec<_>>();
+/// let mut solver = FigureSolver::new(&star, &entries, 26).unwrap();
+///
+/// assert_eq!(solver.count(None), 960);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FigureSolver {
+    values: Vec<i64>,
+    remaining: Vec<usize>,
+    constraint: LineConstraint,
+    cells: Vec<Option<usize>>,
+    lines: Vec<Vec<usize>>,
+    cell_lines: Vec<Vec<usize>>,
+    line_sum: Vec<i64>,
+    line_empty: Vec<usize>,
+    trail: Vec<usize>,
+    pub stats: CompletionStats,
+}
+
+impl FigureSolver {
+    /// Creates a new solver for a multiset of entries and the sum every line must share.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match the number of cells of the figure.
+    pub fn new(
+        figure: &MagicFigure,
+        entries: &[i64],
+        magic_sum: i64,
+    ) -> Result<Self, ParameterSetError> {
+        if entries.len() != figure.cells() {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match the number of cells!".to_string(),
+            ));
+        }
+
+        Self::with_constraint(figure, entries, LineConstraint::Sum(magic_sum))
+    }
+
+    /// Creates a new solver for a pool of entries and the constraint every line must meet.
+    ///
+    /// The pool may hold more entries than the figure has cells, in which case every
+    /// arrangement uses some of them.
+    ///
+    /// # Errors
+    /// - If the pool holds fewer entries than the figure has cells.
+    /// - If a product constraint is not positive or the pool holds a non-positive entry.
+    pub fn with_constraint(
+        figure: &MagicFigure,
+        entries: &[i64],
+        constraint: LineConstraint,
+    ) -> Result<Self, ParameterSetError> {
+        if entries.len() < figure.cells() {
+            return Err(ParameterSetError::ElementCount(
+                "Item length is less than the number of cells!".to_string(),
+            ));
+        }
+        if let LineConstraint::Product(product) = constraint {
+            if product <= 0 || entries.iter().any(|&x| x <= 0) {
+                return Err(ParameterSetError::InvalidElements(
+                    "Products need positive entries!".to_string(),
+                ));
+            }
+        }
+
+        let mut sorted = entries.to_vec();
+        sorted.sort_unstable();
+        let mut values: Vec<i64> = vec![];
+        let mut remaining = vec![];
+        for x in sorted {
+            if values.last() == Some(&x) {
+                *remaining.last_mut().unwrap_or(&mut 0) += 1;
+            } else {
+                values.push(x);
+                remaining.push(1);
+            }
+        }
+
+        let lines = figure.lines().to_vec();
+        let mut cell_lines = vec![vec![]; figure.cells()];
+        for (l, line) in lines.iter().enumerate() {
+            for &c in line {
+                cell_lines[c].push(l);
+            }
+        }
+
+        Ok(Self {
+            values,
+            remaining,
+            constraint,
+            cells: vec![None; figure.cells()],
+            line_sum: vec![constraint.identity(); lines.len()],
+            line_empty: lines.iter().map(Vec::len).collect(),
+            lines,
+            cell_lines,
+            trail: Vec::with_capacity(figure.cells()),
+            stats: CompletionStats::default(),
+        })
+    }
+
+    /// Fixes the entry of a cell, so that only arrangements with `value` at `cell` are found.
+    ///
+    /// # Errors
+    /// - If the cell is out of range or already fixed.
+    /// - If no unused entry equals `value`, or a product line through `cell` rules it out.
+    pub fn fix(mut self, cell: usize, value: i64) -> Result<Self, ParameterSetError> {
+        if self.cells.get(cell) != Some(&None) {
+            return Err(ParameterSetError::InvalidElements(
+                "Cell is out of range or already fixed!".to_string(),
+            ));
+        }
+
+        match self.values.binary_search(&value) {
+            Ok(v) if self.remaining[v] > 0 && self.fits(cell, value) => {
+                self.assign(cell, v);
+                self.trail.clear();
+                Ok(self)
+            }
+            _ => Err(ParameterSetError::InvalidElements(
+                "Value is not an unused entry that fits the cell!".to_string(),
+            )),
+        }
+    }
+
+    /// Returns the sum, or the product, every line of an arrangement shares.
+    #[must_use]
+    pub fn magic_sum(&self) -> i64 {
+        self.constraint.target()
+    }
+
+    /// Returns the constraint every line of an arrangement meets.
+    #[must_use]
+    pub fn constraint(&self) -> LineConstraint {
+        self.constraint
+    }
+
+    /// Finds one magic arrangement, if any exists.
+    pub fn solve(&mut self) -> Option<Vec<i64>> {
+        let mut found = None;
+        self.search(&mut |s| {
+            found = Some(s);
+            false
+        });
+        found
+    }
+
+    /// Finds every magic arrangement.
+    pub fn solve_all(&mut self) -> Vec<Vec<i64>> {
+        let mut found = vec![];
+        self.search(&mut |s| {
+            found.push(s);
+            true
+        });
+        found
+    }
+
+    /// Counts the magic arrangements, stopping early once `limit` arrangements have been found.
+    pub fn count(&mut self, limit: Option<u64>) -> u64 {
+        let mut count = 0;
+        self.search(&mut |_| {
+            count += 1;
+            limit.is_none_or(|l| count < l)
+        });
+        count
+    }
+
+    /// Runs the search from the fixed cells, calling `on_solution` with each arrangement.
+    /// The search stops as soon as `on_solution` returns `false`.
+    pub(crate) fn search(&mut self, on_solution: &mut dyn FnMut(Vec<i64>) -> bool) {
+        let mark = self.trail.len();
+        if self.propagate() {
+            self.branch(on_solution);
+        }
+        self.undo(mark);
+    }
+
+    /// Returns `false` if the search should stop.
+    fn branch(&mut self, on_solution: &mut dyn FnMut(Vec<i64>) -> bool) -> bool {
+        let Some(cell) = self.select_cell() else {
+            let data = self
+                .cells
+                .iter()
+                .map(|&v| self.values[v.unwrap_or_default()]);
+            return on_solution(data.collect());
+        };
+
+        for v in 0..self.values.len() {
+            if self.remaining[v] == 0 || !self.fits(cell, self.values[v]) {
+                continue;
+            }
+
+            self.stats.branches += 1;
+            let mark = self.trail.len();
+            self.assign(cell, v);
+
+            let proceed = !self.propagate() || self.branch(on_solution);
+            self.undo(mark);
+            if !proceed {
+                return false;
+            }
+        }
+
+        self.stats.backtracks += 1;
+        true
+    }
+
+    /// Chooses the empty cell lying on the line with the fewest empty cells.
+    fn select_cell(&self) -> Option<usize> {
+        (0..self.cells.len())
+            .filter(|&c| self.cells[c].is_none())
+            .min_by_key(|&c| {
+                self.cell_lines[c]
+                    .iter()
+                    .map(|&l| self.line_empty[l])
+                    .min()
+                    .unwrap_or(usize::MAX)
+            })
+    }
+
+    /// Fills forced cells until a fixed point is reached. Returns `false` on a contradiction.
+    fn propagate(&mut self) -> bool {
+        loop {
+            let mut forced = None;
+            for l in 0..self.lines.len() {
+                match self.line_empty[l] {
+                    0 if self.line_sum[l] != self.constraint.target() => return false,
+                    0 => {}
+                    1 => {
+                        forced = Some(l);
+                        break;
+                    }
+                    k if !self.completable(l, k) => return false,
+                    _ => {}
+                }
+            }
+
+            let Some(l) = forced else {
+                return true;
+            };
+
+            let value = self.constraint.remainder(self.line_sum[l]);
+            let cell = self.lines[l]
+                .iter()
+                .copied()
+                .find(|&c| self.cells[c].is_none())
+                .unwrap_or_default();
+            let v = match self.values.binary_search(&value) {
+                Ok(v) if self.remaining[v] > 0 && self.fits(cell, value) => v,
+                _ => return false,
+            };
+
+            self.stats.propagations += 1;
+            self.assign(cell, v);
+        }
+    }
+
+    /// Returns `true` if every line through `cell` admits `value`.
+    fn fits(&self, cell: usize, value: i64) -> bool {
+        self.cell_lines[cell]
+            .iter()
+            .all(|&l| self.constraint.admits(self.line_sum[l], value))
+    }
+
+    /// Checks that the remainder of a line lies between the aggregates of the `k` smallest and
+    /// the `k` largest unused entries.
+    fn completable(&self, line: usize, k: usize) -> bool {
+        let remaining = self.constraint.remainder(self.line_sum[line]);
+        let identity = self.constraint.identity();
+        let combine = |acc, x| self.constraint.combine(acc, x);
+
+        let counts = self.values.iter().zip(&self.remaining);
+        let unused = counts
+            .clone()
+            .flat_map(|(&x, &count)| std::iter::repeat_n(x, count));
+        let unused_rev = counts
+            .rev()
+            .flat_map(|(&x, &count)| std::iter::repeat_n(x, count));
+        let low = unused.take(k).fold(identity, combine);
+        let high = unused_rev.take(k).fold(identity, combine);
+
+        low <= remaining && remaining <= high
+    }
+
+    fn assign(&mut self, cell: usize, v: usize) {
+        self.cells[cell] = Some(v);
+        self.remaining[v] -= 1;
+        for &l in &self.cell_lines[cell] {
+            self.line_sum[l] = self.constraint.combine(self.line_sum[l], self.values[v]);
+            self.line_empty[l] -= 1;
+        }
+        self.trail.push(cell);
+    }
+
+    fn undo(&mut self, mark: usize) {
+        while self.trail.len() > mark {
+            let cell = self.trail.pop().unwrap_or_default();
+            let v = self.cells[cell].take().unwrap_or_default();
+            self.remaining[v] += 1;
+            for &l in &self.cell_lines[cell] {
+                self.line_sum[l] = self.constraint.remove(self.line_sum[l], self.values[v]);
+                self.line_empty[l] += 1;
+            }
+        }
+    }
+}
+
+impl MagicFigure {
+    /// Returns every magic arrangement of a multiset of entries on a figure where the sum is
+    /// determined by the entries, as for stars.
+    ///
+    /// # Errors
+    /// - If the entries admit no magic sum, see `MagicFigure::magic_sum_of`.
+    pub fn arrangements(&self, entries: &[i64]) -> Result<Vec<Vec<i64>>, ParameterSetError> {
+        Ok(FigureSolver::new(self, entries, self.magic_sum_of(entries)?)?.solve_all())
+    }
+}
+
+#[cfg(test)]
+mod test_figure {
+    use crate::O3;
+
+    use super::*;
+
+    #[test]
+    fn test_stars() -> Result<(), ParameterSetError> {
+        let pentagram = MagicFigure::pentagram();
+        assert!(pentagram
+            .arrangements(&(1..=10).collect::<Vec<_>>())?
+            .is_empty());
+
+        let entries = [1, 2, 3, 4, 5, 6, 8, 9, 10, 12];
+        let stars = pentagram.arrangements(&entries)?;
+        assert!(!stars.is_empty());
+        assert!(stars.iter().all(|s| pentagram.check_s(s) == Some(24)));
+
+        let hexagram = MagicFigure::hexagram();
+        let stars = hexagram.arrangements(&(1..=12).collect::<Vec<_>>())?;
+        assert_eq!(stars.len(), 80 * 12);
+        assert!(stars.iter().all(|s| hexagram.check_s(s) == Some(26)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_edge_magic() -> Result<(), ParameterSetError> {
+        let triangle = MagicFigure::edge_magic(3, &[(0, 1), (1, 2), (2, 0)])?;
+        let entries = (1..=6).collect::<Vec<_>>();
+
+        let sums = (9..=12)
+            .map(|s| FigureSolver::new(&triangle, &entries, s).map(|mut f| f.count(None)))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert!(sums.iter().all(|&c| c > 0));
+        assert_eq!(FigureSolver::new(&triangle, &entries, 13)?.count(None), 0);
+
+        let solver = FigureSolver::new(&triangle, &entries, 10)?.fix(0, 1)?;
+        assert!(solver.clone().solve().is_some_and(|s| s[0] == 1));
+        assert!(solver.fix(0, 2).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_product() -> Result<(), ParameterSetError> {
+        let square = MagicFigure::square::<O3>();
+
+        // Powers of two multiply like the Lo Shu adds its exponents.
+        let powers = (0..9).map(|e| 1 << e).collect::<Vec<_>>();
+        let mut solver =
+            FigureSolver::with_constraint(&square, &powers, LineConstraint::Product(1 << 12))?;
+        assert_eq!(solver.count(None), 8);
+
+        // A pool of divisors, as searched by `MultiplicativeSquare::with_max_entry`.
+        let divisors = (1..=36).filter(|d| 216 % d == 0).collect::<Vec<_>>();
+        let squares =
+            FigureSolver::with_constraint(&square, &divisors, LineConstraint::Product(216))?
+                .solve_all();
+        assert_eq!(squares.len(), 8);
+        assert!(squares
+            .iter()
+            .all(|s| s.iter().product::<i64>() == 216 * 216 * 216));
+
+        assert!(
+            FigureSolver::with_constraint(&square, &powers[1..], LineConstraint::Product(64))
+                .is_err()
+        );
+        assert!(
+            FigureSolver::with_constraint(&square, &[0; 9], LineConstraint::Product(64)).is_err()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use crate::{Construction, Params, VecSquare, O8};
+
+/// Builds the index sets of the lines of a Franklin square: the rows and columns, their halves,
+/// the bent diagonals and every 2×2 subsquare, wrapping around the edges.
+///
+/// A bent diagonal runs across the square and turns back at its middle, like a `V`. There are
+/// `P::ORDER` in each of the four directions, moved along its axis and wrapping.
+pub(crate) fn franklin_lines<P: Params>() -> Vec<Vec<usize>> {
+    let n = P::ORDER;
+    let h = n / 2;
+    let bend = |k: usize, i: usize, up: bool| {
+        let d = k.min(n - 1 - k);
+        if up {
+            (i + n - d) % n
+        } else {
+            (i + d) % n
+        }
+    };
+
+    let mut lines: Vec<Vec<usize>> = Vec::new();
+    for i in 0..n {
+        lines.push((0..n).map(|j| i * n + j).collect());
+        lines.push((0..n).map(|j| j * n + i).collect());
+        for half in [0, h] {
+            lines.push((half..half + h).map(|j| i * n + j).collect());
+            lines.push((half..half + h).map(|j| j * n + i).collect());
+        }
+        for up in [false, true] {
+            lines.push((0..n).map(|k| bend(k, i, up) * n + k).collect());
+            lines.push((0..n).map(|k| k * n + bend(k, i, up)).collect());
+        }
+        for j in 0..n {
+            let (i1, j1) = ((i + 1) % n, (j + 1) % n);
+            lines.push(vec![i * n + j, i * n + j1, i1 * n + j, i1 * n + j1]);
+        }
+    }
+
+    lines
+}
+
+/// Benjamin Franklin's Franklin square of order 8.
+const FRANKLIN: [u32; 64] = [
+    52, 61, 4, 13, 20, 29, 36, 45, 14, 3, 62, 51, 46, 35, 30, 19, 53, 60, 5, 12, 21, 28, 37, 44,
+    11, 6, 59, 54, 43, 38, 27, 22, 55, 58, 7, 10, 23, 26, 39, 42, 9, 8, 57, 56, 41, 40, 25, 24, 50,
+    63, 2, 15, 18, 31, 34, 47, 16, 1, 64, 49, 48, 33, 32, 17,
+];
+
+impl Construction<O8> {
+    /// Returns Benjamin Franklin's square of order 8.
+    #[must_use]
+    pub fn franklin() -> Self {
+        Construction {
+            square: VecSquare::from_vec(FRANKLIN.to_vec()),
+        }
+    }
+
+    /// Enumerates Franklin squares of order 8, stopping after `limit` squares if one is given.
+    ///
+    /// Each square is built from its entries less one, written in binary: every bit is a sign
+    /// pattern of a compact square, negating along every row or along every column so each 2×2
+    /// subsquare balances, which also balances every line of a Franklin square. Six such patterns
+    /// give a Franklin square exactly when they split the cells into distinct binary numbers,
+    /// found by a depth first search splitting each class of cells evenly. These are the
+    /// 8,847,360 Franklin squares whose bits are compact, Franklin's own among them.
+    #[must_use]
+    pub fn franklin_squares(limit: Option<usize>) -> Vec<Self> {
+        let mut found = Vec::new();
+        franklin_search(
+            &franklin_planes(),
+            &mut vec![],
+            &[u64::MAX],
+            &mut |planes| {
+                found.push(Self::from_planes(planes));
+                limit.is_none_or(|l| found.len() < l)
+            },
+        );
+
+        found
+    }
+
+    /// Counts the Franklin squares of order 8 enumerated by `franklin_squares`.
+    #[must_use]
+    pub fn count_franklin() -> u64 {
+        let mut count = 0;
+        franklin_search(&franklin_planes(), &mut vec![], &[u64::MAX], &mut |_| {
+            count += 1;
+            true
+        });
+
+        count
+    }
+
+    fn from_planes(planes: &[u64]) -> Self {
+        let data = (0..O8::ELEMENTS)
+            .map(|c| {
+                let bits = planes.iter().rev().fold(0, |acc, p| acc * 2 + (p >> c & 1));
+                bits as u32 + 1
+            })
+            .collect();
+
+        Construction {
+            square: VecSquare::from_vec(data),
+        }
+    }
+}
+
+/// Returns the cells holding `+1` in every sign pattern which negates along every row or along
+/// every column and sums to zero along every line of a Franklin square of order 8.
+fn franklin_planes() -> Vec<u64> {
+    let n = O8::ORDER;
+    let lines = franklin_lines::<O8>();
+    let mut planes = (0..1u64 << n)
+        .flat_map(|r| {
+            let sign = |i: usize, j: usize| (r >> j & 1) ^ (i as u64 & 1);
+            let rows = (0..O8::ELEMENTS).map(|c| sign(c / n, c % n) << c);
+            let cols = (0..O8::ELEMENTS).map(|c| sign(c % n, c / n) << c);
+            [rows.sum::<u64>(), cols.sum::<u64>()]
+        })
+        .filter(|p| {
+            lines.iter().all(|line| {
+                let plus = line.iter().filter(|&&c| p >> c & 1 == 1).count();
+                2 * plus == line.len()
+            })
+        })
+        .collect::<Vec<_>>();
+    planes.sort_unstable();
+    planes.dedup();
+
+    planes
+}
+
+/// Chooses patterns splitting every class of cells evenly until each cell has its own class,
+/// calling `visit` with the patterns and stopping once it returns `false`.
+fn franklin_search(
+    planes: &[u64],
+    chosen: &mut Vec<u64>,
+    classes: &[u64],
+    visit: &mut impl FnMut(&[u64]) -> bool,
+) -> bool {
+    if classes.len() == O8::ELEMENTS {
+        return visit(chosen);
+    }
+
+    for &p in planes {
+        if !classes
+            .iter()
+            .all(|c| 2 * (c & p).count_ones() == c.count_ones())
+        {
+            continue;
+        }
+
+        let split = classes
+            .iter()
+            .flat_map(|c| [c & p, c & !p])
+            .collect::<Vec<_>>();
+        chosen.push(p);
+        let go_on = franklin_search(planes, chosen, &split, visit);
+        chosen.pop();
+        if !go_on {
+            return false;
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test_franklin {
+    use itertools::Itertools;
+
+    use crate::O4;
+
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let lines = franklin_lines::<O8>();
+
+        assert_eq!(lines.len(), 8 * (2 + 4 + 4 + 8));
+        assert!(lines.contains(&vec![0, 9, 18, 27, 35, 42, 49, 56]));
+        assert!(lines.contains(&vec![56, 49, 42, 35, 36, 45, 54, 63]));
+        assert!(lines.contains(&vec![63, 56, 7, 0]));
+        assert_eq!(franklin_lines::<O4>().len(), 4 * (2 + 4 + 4 + 4));
+    }
+
+    #[test]
+    fn test_planes() {
+        let planes = franklin_planes();
+        assert_eq!(planes.len(), 34);
+
+        let franklin = Construction::<O8>::franklin();
+        for b in 0..6 {
+            let plane = (0..64)
+                .filter(|&c| (franklin.square.data[c] - 1) >> b & 1 == 1)
+                .map(|c| 1u64 << c)
+                .sum::<u64>();
+            assert!(planes.contains(&plane));
+        }
+    }
+
+    #[test]
+    fn test_franklin_squares() {
+        let squares = Construction::<O8>::franklin_squares(Some(200));
+
+        assert_eq!(squares.len(), 200);
+        assert!(squares.iter().map(|a| &a.square.data).all_unique());
+        for a in &squares {
+            assert!(a.check_franklin_n_s().is_some());
+            assert_eq!(
+                a.square.data.iter().copied().sorted().collect_vec(),
+                (1..=64).collect_vec()
+            );
+        }
+    }
+
+    #[test]
+    #[ignore = "enumerates every Franklin square of order 8"]
+    fn test_count_franklin() {
+        assert_eq!(Construction::<O8>::count_franklin(), 8_847_360);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{HyperParams, Hypercube, Params};
+
+impl<P: Params, const D: usize> Hypercube<P, D> {
+    /// Creates a simple magic hypercube of odd order, generalising the Siamese method.
+    ///
+    /// The Siamese method writes `1, 2, 3, ...` along a fixed diagonal step, which is the same as
+    /// giving each base `n` digit of `x - 1` as a linear function of the cell's coordinates. Here
+    /// digit `t` is `sum_k a_tk * (x_k - m) + m` modulo `n`, with `m` the middle coordinate,
+    /// `a_tk = 1` for `k <= t` and `a_tk = -1` otherwise. Every coefficient is a unit and the
+    /// determinant is `2 ^ (D - 1)`, so each cell gets a distinct entry and every digit runs
+    /// through all residues along an axis-parallel line, while every line through the centre
+    /// keeps each digit balanced about `m`.
+    ///
+    /// # Panics
+    /// - If the order is not odd.
+    #[must_use]
+    pub fn siamese() -> Self {
+        assert!(P::ORDER % 2 != 0, "Order Must Be Odd!");
+
+        let n = P::ORDER;
+        let m = n / 2;
+        let data = (0..<P as HyperParams<D>>::CELLS)
+            .map(|c| {
+                let x = Self::coords(c);
+                let digit = |t: usize| {
+                    let offset = x.iter().enumerate().map(|(k, &xk)| {
+                        let shifted = xk + n - m;
+                        if k <= t {
+                            shifted
+                        } else {
+                            n - shifted % n
+                        }
+                    });
+                    (offset.sum::<usize>() + m) % n
+                };
+                let value = (0..D).rev().fold(0, |acc, t| acc * n + digit(t));
+                value as u32 + 1
+            })
+            .collect();
+
+        Self::new(data).unwrap_or_else(|_| unreachable!("one entry is built for every cell"))
+    }
+}
+
+#[cfg(test)]
+mod test_hypercube {
+    use itertools::Itertools;
+
+    use crate::{O3, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_siamese_square() {
+        let a = Hypercube::<O5, 2>::siamese();
+
+        assert!(a.check_diagonal_n_s().is_some());
+        assert_eq!(
+            a.data.iter().copied().sorted().collect_vec(),
+            (1..=25).collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_siamese_cube() {
+        for a in [
+            Hypercube::<O3, 3>::siamese().data,
+            Hypercube::<O5, 3>::siamese().data,
+        ] {
+            let n = a.len() as u32;
+            assert_eq!(
+                a.iter().copied().sorted().collect_vec(),
+                (1..=n).collect_vec()
+            );
+        }
+
+        assert!(Hypercube::<O3, 3>::siamese().check_n_s().is_some());
+        assert!(Hypercube::<O5, 3>::siamese().check_n_s().is_some());
+    }
+
+    #[test]
+    fn test_siamese_tesseract() {
+        let a = Hypercube::<O3, 4>::siamese();
+        assert!(a.check_n_s().is_some());
+        assert!(Hypercube::<O5, 4>::siamese().check_n_s().is_some());
+    }
+}
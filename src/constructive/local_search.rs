@@ -0,0 +1,659 @@
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::thread;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{Construction, Params, VecSquare};
+
+/// The move acceptance rule used by a `LocalSearchSolver` while fixing the diagonals.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LocalSearchStrategy {
+    /// Propose random swaps and accept worsening ones with the Metropolis probability. The
+    /// temperature starts at `temperature` and is multiplied by `cooling` after each proposal.
+    Annealing { temperature: f64, cooling: f64 },
+    /// Take the best swap of two non-tabu rows or columns. The rows or columns of a swap that
+    /// does not lower the cost are made tabu for `tenure` iterations, and once `reset_limit` of
+    /// them are tabu a `reset_fraction` of the order is spent on random swaps.
+    Tabu {
+        tenure: u64,
+        reset_limit: usize,
+        reset_fraction: f64,
+    },
+}
+
+/// A heuristic solver that searches for magic squares of any order by local search over cell
+/// swaps.
+///
+/// Each chain starts from a shuffled square. The cost of a square is the total deviation of its
+/// constraint vectors from `P::MAGIC_SUM`, which the chain lowers to zero by swapping cells: the
+/// rows and columns are repaired by exchanging cells between pairs of rows and pairs of columns,
+/// and the diagonals are then fixed by swapping whole rows and columns according to the
+/// `LocalSearchStrategy`. A chain that has not reached zero cost after `iterations` moves is
+/// restarted from a fresh shuffle, up to `restarts` times. With more than one thread, independent
+/// chains run in parallel and the first magic square found is returned.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{LocalSearchSolver, O20};
+///
+/// let a = LocalSearchSolver::<O20>::new().seed(20).solve().unwrap();
+/// assert!(a.check_n_s().is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocalSearchSolver<P: Params> {
+    seed: Option<u64>,
+    threads: usize,
+    restarts: usize,
+    iterations: u64,
+    strategy: LocalSearchStrategy,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params> Default for LocalSearchSolver<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<P: Params> LocalSearchSolver<P> {
+    /// Creates a new solver using tabu search on a single thread with an entropy seed.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            seed: None,
+            threads: 1,
+            restarts: 1000,
+            iterations: 100 * P::ELEMENTS as u64,
+            strategy: LocalSearchStrategy::Tabu {
+                tenure: 4,
+                reset_limit: 10,
+                reset_fraction: 0.2,
+            },
+            phantom: PhantomData,
+        }
+    }
+
+    /// Switches to simulated annealing, with a schedule and number of iterations suited to the
+    /// order of the square.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn annealing(mut self) -> Self {
+        let (n, elements) = (P::ORDER as f64, P::ELEMENTS as f64);
+        self.iterations = 10_000 * P::ELEMENTS as u64;
+        self.strategy = LocalSearchStrategy::Annealing {
+            temperature: 2.0 * n,
+            cooling: 1.0 - 1.0 / (3000.0 * elements),
+        };
+        self
+    }
+
+    /// Seeds the random number generators. Chain `i` is seeded with `seed + i`.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the number of independent chains run in parallel.
+    #[must_use]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Sets the number of times each chain is restarted from a fresh shuffle.
+    #[must_use]
+    pub fn restarts(mut self, restarts: usize) -> Self {
+        self.restarts = restarts;
+        self
+    }
+
+    /// Sets the number of moves per run of a chain.
+    #[must_use]
+    pub fn iterations(mut self, iterations: u64) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets the move acceptance rule. Annealing moves are much cheaper than tabu moves, so the
+    /// number of iterations should usually be raised along with it.
+    #[must_use]
+    pub fn strategy(mut self, strategy: LocalSearchStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+}
+
+impl<P: Params + Copy + Send + Sync> LocalSearchSolver<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Runs the solver, returning the first magic square found, or `None` if every chain
+    /// exhausted its restarts.
+    #[must_use]
+    pub fn solve(&self) -> Option<Construction<P>> {
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let found = AtomicBool::new(false);
+
+        if self.threads == 1 {
+            return self.run_chain(seed, &found);
+        }
+
+        thread::scope(|s| {
+            let handles = (0..self.threads as u64)
+                .map(|i| {
+                    let found = &found;
+                    s.spawn(move || self.run_chain(seed.wrapping_add(i), found))
+                })
+                .collect::<Vec<_>>();
+
+            handles.into_iter().find_map(|h| h.join().ok().flatten())
+        })
+    }
+
+    fn run_chain(&self, seed: u64, found: &AtomicBool) -> Option<Construction<P>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        for _ in 0..=self.restarts {
+            let mut start = Construction::<P>::identity();
+            start.shuffle_with(&mut rng);
+
+            let mut chain = Chain::<P>::new(start.square.data);
+            let solved = chain.search(self.iterations, self.strategy, &mut rng, found);
+
+            if solved {
+                found.store(true, Relaxed);
+                return Some(Construction {
+                    square: VecSquare::from_vec(chain.data),
+                });
+            }
+            if found.load(Relaxed) {
+                return None;
+            }
+        }
+
+        None
+    }
+}
+
+/// The families of constraint vectors, in the order a chain satisfies them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Rows,
+    Columns,
+    Diagonals,
+}
+
+/// A swap of two whole rows or two whole columns, which keeps every row and column sum intact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Move {
+    Rows(usize, usize),
+    Columns(usize, usize),
+}
+
+impl Move {
+    /// The rows or columns a move swaps, with column `c` stored at `P::ORDER + c`.
+    fn lines<P: Params>(self) -> [usize; 2] {
+        match self {
+            Move::Rows(a, b) => [a, b],
+            Move::Columns(a, b) => [P::ORDER + a, P::ORDER + b],
+        }
+    }
+}
+
+/// State of a single search chain, with the running sums of every constraint vector.
+///
+/// A chain satisfies the rows, then the columns, then the diagonals, keeping the families
+/// already satisfied intact. In each phase the cost is the total deviation of the satisfied
+/// families together with the current one, so once the diagonals are satisfied it is the total
+/// deviation of every constraint vector.
+///
+/// Single swaps only move a deviation between two rows or columns when some column or row holds
+/// two values differing by exactly that deviation, which leaves a shuffled square stuck close to
+/// semi-magic. Rows are therefore repaired pairwise by exchanging cells between two rows within
+/// a subset of the columns, chosen by subset sum, and columns likewise within a subset of the
+/// rows. The diagonals are then searched with the `LocalSearchStrategy`, over swaps of whole rows
+/// or columns.
+struct Chain<P: Params> {
+    data: Vec<u32>,
+    sums: Vec<i64>,
+    cost: i64,
+    phase: Phase,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params> Chain<P> {
+    fn new(data: Vec<u32>) -> Self {
+        let mut sums = vec![0; P::CONSTRAINT_VECTORS];
+        for (cell, &v) in data.iter().enumerate() {
+            for line in Self::lines(cell).into_iter().flatten() {
+                sums[line] += i64::from(v);
+            }
+        }
+
+        let mut chain = Self {
+            data,
+            sums,
+            cost: 0,
+            phase: Phase::Rows,
+            phantom: PhantomData,
+        };
+        chain.set_phase(Phase::Diagonals);
+        chain
+    }
+
+    /// Indexes of the constraint vectors containing a cell: its row, its column and any diagonal.
+    fn lines(cell: usize) -> [Option<usize>; 4] {
+        let (r, c) = (cell / P::ORDER, cell % P::ORDER);
+        [
+            Some(r),
+            Some(P::ORDER + c),
+            (r == c).then_some(2 * P::ORDER),
+            (r + c == P::ORDER - 1).then_some(2 * P::ORDER + 1),
+        ]
+    }
+
+    /// Number of constraint vectors counted by the cost in the current phase.
+    fn active(&self) -> usize {
+        match self.phase {
+            Phase::Rows => P::ORDER,
+            Phase::Columns => 2 * P::ORDER,
+            Phase::Diagonals => P::CONSTRAINT_VECTORS,
+        }
+    }
+
+    /// Deviation of a constraint vector from `P::MAGIC_SUM` after its sum changes by `change`.
+    fn deviation(&self, line: usize, change: i64) -> i64 {
+        (self.sums[line] + change - i64::from(P::MAGIC_SUM)).abs()
+    }
+
+    fn set_phase(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.cost = (0..self.active()).map(|l| self.deviation(l, 0)).sum();
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        let active = self.active();
+        let diff = i64::from(self.data[b]) - i64::from(self.data[a]);
+        let lines_a = Self::lines(a).into_iter().flatten().map(|l| (l, diff));
+        let lines_b = Self::lines(b).into_iter().flatten().map(|l| (l, -diff));
+        for (l, d) in lines_a.chain(lines_b) {
+            if l < active {
+                self.cost += self.deviation(l, d) - self.deviation(l, 0);
+            }
+            self.sums[l] += d;
+        }
+        self.data.swap(a, b);
+    }
+
+    /// Moves through the phases, returning `true` once every constraint vector is satisfied.
+    fn search<R: Rng>(
+        &mut self,
+        iterations: u64,
+        strategy: LocalSearchStrategy,
+        rng: &mut R,
+        found: &AtomicBool,
+    ) -> bool {
+        if !self.repair(Phase::Rows) || !self.repair(Phase::Columns) {
+            return false;
+        }
+
+        self.set_phase(Phase::Diagonals);
+        match strategy {
+            LocalSearchStrategy::Annealing {
+                temperature,
+                cooling,
+            } => self.anneal(iterations, (temperature, cooling), rng, found),
+            LocalSearchStrategy::Tabu {
+                tenure,
+                reset_limit,
+                reset_fraction,
+            } => self.tabu(
+                iterations,
+                (tenure, reset_limit, reset_fraction),
+                rng,
+                found,
+            ),
+        }
+    }
+
+    /// Satisfies the rows, or the columns of a square whose rows are satisfied, by repeatedly
+    /// moving the deviation of one line into another. Returns `false` if no exchange lowers the
+    /// cost.
+    fn repair(&mut self, phase: Phase) -> bool {
+        let n = P::ORDER;
+        let base = if phase == Phase::Rows { 0 } else { n };
+        self.set_phase(phase);
+
+        while self.cost > 0 {
+            let deviation = |l: usize| self.sums[base + l] - i64::from(P::MAGIC_SUM);
+            let mut lines = (0..n).collect::<Vec<_>>();
+            lines.sort_by_key(|&l| deviation(l));
+
+            // Pair lines with too large a sum with lines with too small a sum, the largest
+            // deviations first, and apply the first exchange lowering the cost.
+            let high = lines.iter().rev().take_while(|&&l| deviation(l) > 0);
+            let low = || lines.iter().take_while(|&&l| deviation(l) < 0);
+            let exchange = high
+                .flat_map(|&a| low().map(move |&b| (a, b)))
+                .find_map(|(a, b)| self.exchange(phase, a, b));
+
+            let Some(cells) = exchange else {
+                return false;
+            };
+            for (a, b) in cells {
+                self.swap(a, b);
+            }
+        }
+
+        true
+    }
+
+    /// Finds the pairs of cells to swap between lines `a` and `b` so as to move as much of the
+    /// excess of `a` into `b` as possible, or `None` if no exchange lowers the cost.
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    fn exchange(&self, phase: Phase, a: usize, b: usize) -> Option<Vec<(usize, usize)>> {
+        let n = P::ORDER;
+        let cell = |line: usize, k: usize| {
+            if phase == Phase::Rows {
+                line * n + k
+            } else {
+                k * n + line
+            }
+        };
+        let base = if phase == Phase::Rows { 0 } else { n };
+        let m = i64::from(P::MAGIC_SUM);
+        let (excess_a, excess_b) = (self.sums[base + a] - m, self.sums[base + b] - m);
+
+        // Swapping the `k`th cells of the lines moves `weights[k]` from `a` to `b`. Subset sums
+        // are stored with an offset so that negative sums index the table, and `first[s]` holds
+        // the cell that first made the sum `s` reachable.
+        let weights = (0..n)
+            .map(|k| i64::from(self.data[cell(a, k)]) - i64::from(self.data[cell(b, k)]))
+            .collect::<Vec<_>>();
+        let offset = weights.iter().map(|w| w.abs()).sum::<i64>();
+        let width = 2 * offset as usize + 1;
+
+        let mut reachable = vec![false; width];
+        let mut first = vec![usize::MAX; width];
+        reachable[offset as usize] = true;
+        for (k, &w) in weights.iter().enumerate() {
+            let before = reachable.clone();
+            for s in (0..width).filter(|&s| before[s]) {
+                let t = (s as i64 + w) as usize;
+                if !reachable[t] {
+                    reachable[t] = true;
+                    first[t] = k;
+                }
+            }
+        }
+
+        let gain = |moved: i64| {
+            excess_a.abs() + excess_b.abs() - (excess_a - moved).abs() - (excess_b + moved).abs()
+        };
+        let (mut s, _) = (0..width)
+            .filter(|&s| reachable[s])
+            .map(|s| (s, gain(s as i64 - offset)))
+            .filter(|&(_, g)| g > 0)
+            .max_by_key(|&(_, g)| g)?;
+
+        let mut cells = vec![];
+        while s != offset as usize {
+            let k = first[s];
+            cells.push((cell(a, k), cell(b, k)));
+            s = (s as i64 - weights[k]) as usize;
+        }
+
+        Some(cells)
+    }
+
+    /// Changes to the two diagonal sums made by a move.
+    fn diagonal_changes(&self, mv: Move) -> (i64, i64) {
+        let n = P::ORDER;
+        let (i, j, transpose) = match mv {
+            Move::Rows(i, j) => (i, j, false),
+            Move::Columns(i, j) => (i, j, true),
+        };
+        let get = |r: usize, c: usize| {
+            let cell = if transpose { c * n + r } else { r * n + c };
+            i64::from(self.data[cell])
+        };
+
+        (
+            get(j, i) + get(i, j) - get(i, i) - get(j, j),
+            get(j, n - 1 - i) + get(i, n - 1 - j) - get(i, n - 1 - i) - get(j, n - 1 - j),
+        )
+    }
+
+    /// Change in cost made by a move.
+    fn delta(&self, mv: Move) -> i64 {
+        let (main, anti) = self.diagonal_changes(mv);
+        let (d, a) = (2 * P::ORDER, 2 * P::ORDER + 1);
+        self.deviation(d, main) - self.deviation(d, 0) + self.deviation(a, anti)
+            - self.deviation(a, 0)
+    }
+
+    fn apply(&mut self, mv: Move) {
+        let n = P::ORDER;
+        match mv {
+            Move::Rows(i, j) => (0..n).for_each(|c| self.swap(i * n + c, j * n + c)),
+            Move::Columns(i, j) => (0..n).for_each(|r| self.swap(r * n + i, r * n + j)),
+        }
+    }
+
+    /// Draws a uniformly random swap of two rows or two columns.
+    fn random_move<R: Rng>(rng: &mut R) -> Move {
+        let (i, j) = loop {
+            let (i, j) = (rng.gen_range(0..P::ORDER), rng.gen_range(0..P::ORDER));
+            if i != j {
+                break (i, j);
+            }
+        };
+
+        if rng.gen_bool(0.5) {
+            Move::Rows(i, j)
+        } else {
+            Move::Columns(i, j)
+        }
+    }
+
+    /// Runs one annealing schedule on the diagonals. Returns `true` if the chain reached a magic
+    /// square.
+    #[allow(clippy::cast_precision_loss)]
+    fn anneal<R: Rng>(
+        &mut self,
+        iterations: u64,
+        (mut temperature, cooling): (f64, f64),
+        rng: &mut R,
+        found: &AtomicBool,
+    ) -> bool {
+        for i in 0..iterations {
+            if self.cost == 0 {
+                return true;
+            }
+            if i % 4096 == 0 && found.load(Relaxed) {
+                return false;
+            }
+
+            let mv = Self::random_move(rng);
+            let delta = self.delta(mv);
+            if delta <= 0 || rng.gen::<f64>() < (-(delta as f64) / temperature).exp() {
+                self.apply(mv);
+            }
+
+            temperature = (temperature * cooling).max(0.05);
+        }
+
+        self.cost == 0
+    }
+
+    /// Runs one tabu search on the diagonals. Returns `true` if the chain reached a magic square.
+    #[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+    fn tabu<R: Rng>(
+        &mut self,
+        iterations: u64,
+        (tenure, reset_limit, reset_fraction): (u64, usize, f64),
+        rng: &mut R,
+        found: &AtomicBool,
+    ) -> bool {
+        let n = P::ORDER;
+        let mut tabu_until = vec![0; 2 * n];
+        let mut tabu_count = 0;
+
+        for i in 1..=iterations {
+            if self.cost == 0 {
+                return true;
+            }
+            if i % 256 == 0 && found.load(Relaxed) {
+                return false;
+            }
+
+            // The best swap of two non-tabu rows or columns, with ties broken at random.
+            let moves = (0..n)
+                .flat_map(|x| (x + 1..n).flat_map(move |y| [Move::Rows(x, y), Move::Columns(x, y)]))
+                .filter(|&mv| mv.lines::<P>().iter().all(|&l| tabu_until[l] <= i));
+
+            match choose_max(rng, moves.map(|mv| (mv, -self.delta(mv)))) {
+                Some((mv, gain)) if gain > 0 => self.apply(mv),
+                Some((mv, 0)) if rng.gen_bool(0.5) => self.apply(mv),
+                Some((mv, _)) => {
+                    for l in mv.lines::<P>() {
+                        tabu_until[l] = i + tenure;
+                    }
+                    tabu_count += 2;
+                }
+                None => tabu_count = reset_limit,
+            }
+
+            if tabu_count >= reset_limit {
+                let swaps = ((n as f64 * reset_fraction) as usize).max(1);
+                for _ in 0..swaps {
+                    self.apply(Self::random_move(rng));
+                }
+                tabu_until.fill(0);
+                tabu_count = 0;
+            }
+        }
+
+        self.cost == 0
+    }
+}
+
+/// Picks the item with the largest key, breaking ties uniformly at random.
+fn choose_max<R: Rng, T: Copy>(
+    rng: &mut R,
+    items: impl Iterator<Item = (T, i64)>,
+) -> Option<(T, i64)> {
+    let mut best: Option<(T, i64)> = None;
+    let mut ties = 0;
+    for (item, key) in items {
+        match best {
+            Some((_, k)) if key < k => {}
+            Some((_, k)) if key == k => {
+                ties += 1;
+                if rng.gen_range(0..ties) == 0 {
+                    best = Some((item, key));
+                }
+            }
+            _ => {
+                best = Some((item, key));
+                ties = 1;
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod test_local_search {
+    use crate::{O10, O3, O30, O4, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_tabu_small() {
+        let a = LocalSearchSolver::<O3>::new().seed(3).solve().unwrap();
+        let b = LocalSearchSolver::<O4>::new().seed(4).solve().unwrap();
+        let c = LocalSearchSolver::<O5>::new().seed(5).solve().unwrap();
+
+        assert!(a.check_n_s().is_some());
+        assert!(b.check_n_s().is_some());
+        assert!(c.check_n_s().is_some());
+    }
+
+    #[test]
+    fn test_anneal() {
+        let a = LocalSearchSolver::<O5>::new().seed(5).annealing().solve();
+        let b = LocalSearchSolver::<O10>::new().seed(10).annealing().solve();
+
+        assert!(a.unwrap().check_n_s().is_some());
+        assert!(b.unwrap().check_n_s().is_some());
+    }
+
+    #[test]
+    fn test_large() {
+        let a = LocalSearchSolver::<O30>::new().seed(30).solve().unwrap();
+        assert!(a.check_n_s().is_some());
+    }
+
+    #[test]
+    fn test_seeded() {
+        let a = LocalSearchSolver::<O10>::new().seed(11).solve();
+        let b = LocalSearchSolver::<O10>::new().seed(11).solve();
+
+        assert!(a.is_some());
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_threads() {
+        let a = LocalSearchSolver::<O10>::new()
+            .seed(10)
+            .threads(4)
+            .solve()
+            .unwrap();
+
+        assert!(a.check_n_s().is_some());
+    }
+
+    #[test]
+    fn test_chain_cost() {
+        let chain = Chain::<O3>::new(vec![2, 7, 6, 9, 5, 1, 4, 3, 8]);
+        assert_eq!(chain.cost, 0);
+
+        let mut chain = Chain::<O3>::new((1..=9).collect());
+        assert_eq!(chain.cost, 24);
+
+        chain.swap(0, 8);
+        assert_eq!(chain.cost, 12);
+        assert_eq!(chain.cost, Chain::<O3>::new(chain.data.clone()).cost);
+    }
+
+    #[test]
+    fn test_chain_moves() {
+        let mut chain = Chain::<O5>::new((1..=25).rev().collect());
+        for mv in [Move::Rows(0, 4), Move::Columns(1, 2), Move::Rows(2, 3)] {
+            let cost = chain.cost + chain.delta(mv);
+            chain.apply(mv);
+
+            assert_eq!(chain.cost, cost);
+            assert_eq!(chain.cost, Chain::<O5>::new(chain.data.clone()).cost);
+        }
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut start = Construction::<O10>::identity();
+        start.shuffle_with(&mut StdRng::seed_from_u64(1));
+        let mut chain = Chain::<O10>::new(start.square.data);
+
+        assert!(chain.repair(Phase::Rows));
+        assert!(chain.repair(Phase::Columns));
+        assert_eq!(chain.cost, 0);
+        assert!(chain.sums[..20]
+            .iter()
+            .all(|&s| s == i64::from(O10::MAGIC_SUM)));
+    }
+}
@@ -1,4 +1,26 @@
+mod anti_magic;
+mod arrange;
+mod completion;
 mod construction;
+mod count;
+mod figure;
+mod franklin;
+mod hypercube;
+mod local_search;
+mod most_perfect;
+mod multiplicative;
+mod puzzle;
 mod random;
+mod rectangle;
+mod series;
 
+pub use anti_magic::*;
+pub use arrange::*;
+pub use completion::*;
 pub use construction::*;
+pub use count::*;
+pub use figure::*;
+pub(crate) use franklin::franklin_lines;
+pub use local_search::*;
+pub use puzzle::*;
+pub use series::*;
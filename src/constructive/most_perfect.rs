@@ -0,0 +1,261 @@
+use std::collections::BTreeSet;
+
+use itertools::Itertools;
+
+use crate::{Construction, Params, VecSquare};
+
+impl<P: Params + Copy> Construction<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Enumerates the reversible squares of doubly even order, stopping after `limit` squares if
+    /// one is given.
+    ///
+    /// A reversible square holds `1 + r_i + c_j` in row `i` and column `j`, so the opposite
+    /// corners of every rectangle share a sum. The row terms and the column terms each take
+    /// their values once, every entry is distinct, and terms at mirrored positions `i` and
+    /// `n - 1 - i` share a sum. The sets of terms are those built by the ordered factorisations
+    /// of `n²`, whose factors are given to the rows and the columns in turn.
+    ///
+    /// # Panics
+    /// - If the order is not a multiple of 4.
+    #[must_use]
+    pub fn reversible_squares(limit: Option<usize>) -> Vec<Self> {
+        let mut found = Vec::new();
+        visit_reversible::<P>(&mut |data| {
+            found.push(Self::from_data(data.to_vec()));
+            limit.is_none_or(|l| found.len() < l)
+        });
+
+        found
+    }
+
+    /// Transforms a reversible square into a most-perfect square by the bijection of Ollerenshaw
+    /// and Brée: reverse the right half of every row and the bottom half of every column, then
+    /// take cell `(i, j)` from cell `(i + n/2 j, n/2 i + j)`, modulo `n`.
+    #[must_use]
+    pub fn reversible_to_most_perfect(&self) -> Self {
+        Self::from_data(most_perfect::<P>(&self.square.data))
+    }
+
+    /// Enumerates the essentially different most-perfect squares of doubly even order, stopping
+    /// after `limit` squares if one is given.
+    ///
+    /// A most-perfect square is pandiagonal, every 2×2 subsquare, wrapping around the edges,
+    /// sums to `2 * (P::ELEMENTS + 1)`, and entries half the order apart along a diagonal sum
+    /// to `P::ELEMENTS + 1`. Each is built from a reversible square and kept if it is the least
+    /// of its rotations and reflections, read row by row.
+    ///
+    /// # Panics
+    /// - If the order is not a multiple of 4.
+    #[must_use]
+    pub fn most_perfect_squares(limit: Option<usize>) -> Vec<Self> {
+        let mut found = Vec::new();
+        visit_reversible::<P>(&mut |data| {
+            let square = most_perfect::<P>(data);
+            if is_least_symmetry(&square, P::ORDER) {
+                found.push(Self::from_data(square));
+            }
+            limit.is_none_or(|l| found.len() < l)
+        });
+
+        found
+    }
+
+    /// Counts the essentially different most-perfect squares of doubly even order without
+    /// building them: every pair of term sets gives `((n/2)! 2^(n/2))²` reversible squares, and
+    /// each most-perfect square has 8 distinct rotations and reflections.
+    ///
+    /// # Panics
+    /// - If the order is not a multiple of 4.
+    #[must_use]
+    pub fn count_most_perfect() -> u64 {
+        assert!(P::ORDER % 4 == 0, "Order Must Be Doubly Even!");
+
+        let h = P::ORDER as u64 / 2;
+        let orders = (1..=h).product::<u64>() << h;
+        term_sets(P::ORDER).len() as u64 * orders * orders / 8
+    }
+
+    fn from_data(data: Vec<u32>) -> Self {
+        Construction {
+            square: VecSquare::from_vec(data),
+        }
+    }
+}
+
+/// Calls `visit` with the entries of every reversible square of order `P::ORDER`, stopping once
+/// it returns `false`.
+fn visit_reversible<P: Params>(visit: &mut impl FnMut(&[u32]) -> bool) {
+    assert!(P::ORDER % 4 == 0, "Order Must Be Doubly Even!");
+
+    let n = P::ORDER;
+    let mut data = vec![0; P::ELEMENTS];
+    for (rows, cols) in term_sets(n) {
+        let cols = mirrored_orders(&cols);
+        for r in mirrored_orders(&rows) {
+            for c in &cols {
+                for (cell, entry) in data.iter_mut().enumerate() {
+                    *entry = 1 + r[cell / n] + c[cell % n];
+                }
+                if !visit(&data) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the pairs of sets of `n` terms whose sums cover `0..n²` once each.
+fn term_sets(n: usize) -> BTreeSet<(Vec<u32>, Vec<u32>)> {
+    fn split(
+        sets: [Vec<u32>; 2],
+        step: u32,
+        rest: u32,
+        turn: usize,
+        n: usize,
+        found: &mut BTreeSet<(Vec<u32>, Vec<u32>)>,
+    ) {
+        if rest == 1 {
+            if sets.iter().all(|s| s.len() == n) {
+                let [a, b] = sets.map(|s| s.into_iter().sorted().collect());
+                found.insert((a, b));
+            }
+            return;
+        }
+
+        for f in (2..=rest).filter(|f| rest.is_multiple_of(*f)) {
+            if sets[turn].len() * f as usize > n {
+                break;
+            }
+            let mut next = sets.clone();
+            next[turn] = sets[turn]
+                .iter()
+                .flat_map(|&x| (0..f).map(move |k| x + k * step))
+                .collect();
+            split(next, step * f, rest / f, 1 - turn, n, found);
+        }
+    }
+
+    let mut found = BTreeSet::new();
+    #[allow(clippy::cast_possible_truncation)]
+    let elements = (n * n) as u32;
+    for turn in [0, 1] {
+        split([vec![0], vec![0]], 1, elements, turn, n, &mut found);
+    }
+
+    found
+}
+
+/// Returns every order of a set of terms in which terms at mirrored positions share a sum.
+fn mirrored_orders(terms: &[u32]) -> Vec<Vec<u32>> {
+    let n = terms.len();
+    let top = terms[n - 1];
+    let low = &terms[..n / 2];
+
+    low.iter()
+        .permutations(n / 2)
+        .flat_map(|pairs| {
+            (0..1usize << (n / 2)).map(move |flips| {
+                let left = pairs
+                    .iter()
+                    .enumerate()
+                    .map(|(k, &&x)| if flips >> k & 1 == 1 { top - x } else { x })
+                    .collect_vec();
+                let right = left.iter().rev().map(|x| top - x);
+                left.iter().copied().chain(right).collect()
+            })
+        })
+        .collect()
+}
+
+fn most_perfect<P: Params>(reversible: &[u32]) -> Vec<u32> {
+    let n = P::ORDER;
+    let h = n / 2;
+    let unfold = |x: usize| if x < h { x } else { n - 1 + h - x };
+
+    (0..P::ELEMENTS)
+        .map(|cell| {
+            let (i, j) = (cell / n, cell % n);
+            let (a, b) = ((i + h * j) % n, (h * i + j) % n);
+            reversible[unfold(a) * n + unfold(b)]
+        })
+        .collect()
+}
+
+/// Whether a square is no greater, read row by row, than any of its rotations and reflections.
+fn is_least_symmetry(data: &[u32], n: usize) -> bool {
+    (1..8).all(|t| {
+        let image = (0..n * n).map(|cell| {
+            let (mut i, mut j) = (cell / n, cell % n);
+            if t & 1 == 1 {
+                (i, j) = (j, i);
+            }
+            if t & 2 == 2 {
+                i = n - 1 - i;
+            }
+            if t & 4 == 4 {
+                j = n - 1 - j;
+            }
+            data[i * n + j]
+        });
+        data.iter().copied().cmp(image).is_le()
+    })
+}
+
+#[cfg(test)]
+mod test_most_perfect {
+    use crate::{Hypercube, O12, O4, O8};
+
+    use super::*;
+
+    #[test]
+    fn test_term_sets() {
+        assert_eq!(term_sets(4).len(), 6);
+        assert_eq!(term_sets(8).len(), 20);
+        assert!(term_sets(4).contains(&(vec![0, 1, 4, 5], vec![0, 2, 8, 10])));
+        assert_eq!(mirrored_orders(&[0, 1, 2, 3]).len(), 8);
+    }
+
+    #[test]
+    fn test_order_4() {
+        assert_eq!(Construction::<O4>::reversible_squares(None).len(), 384);
+
+        let squares = Construction::<O4>::most_perfect_squares(None);
+        assert_eq!(squares.len(), 48);
+        for a in &squares {
+            assert!(a.check_n_s().is_some());
+            assert!(a.check_most_perfect_n_s().is_some());
+            assert!(Hypercube::<O4, 2>::from(a).check_perfect_n_s().is_some());
+        }
+    }
+
+    #[test]
+    fn test_larger_orders() {
+        for a in Construction::<O8>::reversible_squares(Some(50)) {
+            let b = a.reversible_to_most_perfect();
+            assert!(b.check_most_perfect_n_s().is_some());
+            assert!(Hypercube::<O8, 2>::from(&b).check_perfect_n_s().is_some());
+        }
+
+        let c = &Construction::<O12>::most_perfect_squares(Some(1))[0];
+        assert!(c.check_n_s().is_some());
+        assert!(c.check_most_perfect_n_s().is_some());
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(Construction::<O4>::count_most_perfect(), 48);
+        assert_eq!(Construction::<O8>::count_most_perfect(), 368_640);
+        assert_eq!(Construction::<O12>::count_most_perfect(), 22_295_347_200);
+    }
+
+    #[test]
+    #[ignore = "builds every reversible square of order 8"]
+    fn test_enumerate_order_8() {
+        assert_eq!(
+            Construction::<O8>::most_perfect_squares(None).len(),
+            368_640
+        );
+    }
+}
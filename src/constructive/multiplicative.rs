@@ -0,0 +1,132 @@
+use std::collections::BTreeSet;
+
+use itertools::Itertools;
+
+use crate::{FigureSolver, LineConstraint, MagicFigure, MultiplicativeSquare, Params};
+
+/// Returns the divisors of `product` no larger than `max`, in ascending order.
+fn divisors_up_to(product: i64, max: i64) -> Vec<i64> {
+    (1..=max).filter(|&d| product % d == 0).collect()
+}
+
+impl<P: Params + Copy> MultiplicativeSquare<P> {
+    /// Searches for multiplicative magic squares of distinct positive entries whose largest
+    /// entry is `max_entry`, stopping once `limit` squares have been found.
+    ///
+    /// The row holding `max_entry` multiplies it by `P::ORDER - 1` smaller distinct entries, so
+    /// every product of that form is tried as the magic product, in ascending order. Squares
+    /// are reported in every orientation. The search runs on a `FigureSolver` with a
+    /// `LineConstraint::Product`, so products beyond `i64::MAX` are skipped.
+    #[must_use]
+    pub fn with_max_entry(max_entry: u128, limit: Option<usize>) -> Vec<Self> {
+        let mut found = vec![];
+        if max_entry < P::ELEMENTS as u128 {
+            return found;
+        }
+        let Ok(max_entry) = i64::try_from(max_entry) else {
+            return found;
+        };
+
+        let products = (1..max_entry)
+            .combinations(P::ORDER - 1)
+            .filter_map(|c| c.into_iter().try_fold(max_entry, i64::checked_mul))
+            .collect::<BTreeSet<_>>();
+
+        for product in products {
+            let values = divisors_up_to(product, max_entry);
+            if values.len() < P::ELEMENTS || !Self::admits_product(product, &values) {
+                continue;
+            }
+
+            let mut solver = FigureSolver::with_constraint(
+                &MagicFigure::square::<P>(),
+                &values,
+                LineConstraint::Product(product),
+            )
+            .unwrap_or_else(|_| unreachable!("The divisors are positive and fill the square!"));
+            let mut proceed = true;
+            solver.search(&mut |data| {
+                if !data.contains(&max_entry) {
+                    return true;
+                }
+                let data = data.into_iter().map(|x| u128::from(x.unsigned_abs()));
+                if let Ok(square) = MultiplicativeSquare::new(data.collect()) {
+                    found.push(square);
+                    proceed = limit.is_none_or(|l| found.len() < l);
+                }
+                proceed
+            });
+            if !proceed {
+                break;
+            }
+        }
+
+        found
+    }
+
+    /// Checks that `P::ELEMENTS` of the divisors `values` can multiply to the product of the
+    /// whole square, `product` raised to `P::ORDER`, which lies between the products of the
+    /// smallest and the largest of them.
+    fn admits_product(product: i64, values: &[i64]) -> bool {
+        let total = |v: &[i64]| {
+            v.iter().fold(1u128, |acc, &x| {
+                acc.saturating_mul(u128::from(x.unsigned_abs()))
+            })
+        };
+        let square = (0..P::ORDER).fold(1u128, |acc, _| {
+            acc.saturating_mul(u128::from(product.unsigned_abs()))
+        });
+
+        total(&values[..P::ELEMENTS]) <= square
+            && square <= total(&values[values.len() - P::ELEMENTS..])
+    }
+
+    /// Finds a multiplicative magic square of distinct positive entries with the smallest
+    /// possible largest entry, trying each bound from `P::ELEMENTS` upwards.
+    ///
+    /// This is an exhaustive search, practical for orders 3 and 4.
+    #[must_use]
+    pub fn minimal() -> Self {
+        (P::ELEMENTS as u128..)
+            .find_map(|max_entry| Self::with_max_entry(max_entry, Some(1)).pop())
+            .unwrap_or_else(|| unreachable!("exponentiated squares exist for every order"))
+    }
+}
+
+#[cfg(test)]
+mod test_multiplicative_search {
+    use crate::{CheckScalar, O3, O4};
+
+    use super::*;
+
+    #[test]
+    fn test_minimal_3() {
+        let a = MultiplicativeSquare::<O3>::minimal();
+
+        assert_eq!(a.max_entry(), 36);
+        assert_eq!(a.magic_product(), Some(216));
+        assert!(a.check_s().is_some());
+        assert!(a.is_distinct());
+    }
+
+    #[test]
+    fn test_with_max_entry_3() {
+        let squares = MultiplicativeSquare::<O3>::with_max_entry(36, None);
+
+        // The eight orientations of the minimal square, then of a second one with product 1728.
+        assert_eq!(squares.len(), 16);
+        assert!(squares[..8].iter().all(|s| s.magic_product() == Some(216)));
+        assert!(squares[8..].iter().all(|s| s.magic_product() == Some(1728)));
+        assert!(squares.iter().all(|s| s.check_s().is_some()));
+        assert!(MultiplicativeSquare::<O3>::with_max_entry(35, None).is_empty());
+    }
+
+    #[test]
+    fn test_minimal_4() {
+        let a = MultiplicativeSquare::<O4>::minimal();
+
+        assert_eq!(a.max_entry(), 28);
+        assert_eq!(a.magic_product(), Some(5040));
+        assert!(a.check_s().is_some());
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{CompletionSolver, CompletionStats, Construction, ParameterSetError, Params};
+
+/// Difficulty of a puzzle, rated by how much of its solution follows from propagating the
+/// constraint vectors alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    /// Solved by propagation alone.
+    Easy,
+    /// Needs no more guesses than forced cells.
+    Medium,
+    /// Needs more guesses than forced cells.
+    Hard,
+}
+
+impl Difficulty {
+    /// Rates a puzzle from the work a `CompletionSolver` did to solve it.
+    #[must_use]
+    pub fn from_stats(stats: &CompletionStats) -> Self {
+        if stats.branches == 0 {
+            Difficulty::Easy
+        } else if stats.branches <= stats.propagations {
+            Difficulty::Medium
+        } else {
+            Difficulty::Hard
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "Easy"),
+            Difficulty::Medium => write!(f, "Medium"),
+            Difficulty::Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+/// A partially filled square with a unique magic completion, together with that completion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle<P: Params>
+where
+    [(); P::ELEMENTS]:,
+{
+    pub puzzle: Construction<P>,
+    pub solution: Construction<P>,
+    pub stats: CompletionStats,
+    pub difficulty: Difficulty,
+}
+
+impl<P: Params> Puzzle<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Returns the number of filled cells in the puzzle.
+    #[must_use]
+    pub fn clues(&self) -> usize {
+        self.puzzle.square.data.iter().filter(|&&v| v != 0).count()
+    }
+}
+
+impl<P: Params + Copy> Construction<P>
+where
+    [(); P::ELEMENTS]:,
+    [(); P::ORDER]:,
+{
+    /// Generates a puzzle by removing cells from a magic construction, in an order drawn from
+    /// `rng`, for as long as the remaining clues determine a unique magic completion.
+    ///
+    /// Removal stops once `target_clues` clues remain. Every cell is tried at most once, so the
+    /// result may have more clues than the target when no further cell can be removed.
+    ///
+    /// # Errors
+    /// - If the construction is not magic.
+    pub fn puzzle<R: Rng>(
+        &self,
+        target_clues: usize,
+        rng: &mut R,
+    ) -> Result<Puzzle<P>, ParameterSetError> {
+        let solution = self.check_n_s().ok_or_else(|| {
+            ParameterSetError::InvalidElements("Construction is not magic!".to_string())
+        })?;
+
+        let mut order = (0..P::ELEMENTS).collect::<Vec<_>>();
+        order.shuffle(rng);
+
+        let mut puzzle = solution.clone();
+        let mut clues = P::ELEMENTS;
+        for cell in order {
+            if clues <= target_clues {
+                break;
+            }
+
+            let value = puzzle.square.data[cell];
+            puzzle.square.data[cell] = 0;
+            if CompletionSolver::new(&puzzle)?.count(Some(2)) == 1 {
+                clues -= 1;
+            } else {
+                puzzle.square.data[cell] = value;
+            }
+        }
+
+        let mut solver = CompletionSolver::new(&puzzle)?;
+        solver.solve();
+        let stats = solver.stats();
+
+        Ok(Puzzle {
+            puzzle,
+            solution,
+            stats,
+            difficulty: Difficulty::from_stats(&stats),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_puzzle {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{O3, O4, O5, O6};
+
+    use super::*;
+
+    fn assert_unique<P: Params + Copy + PartialEq + std::fmt::Debug>(p: &Puzzle<P>)
+    where
+        [(); P::ELEMENTS]:,
+        [(); P::ORDER]:,
+    {
+        let solutions = p.puzzle.completions();
+        assert_eq!(solutions, vec![p.solution.clone()]);
+        assert!(p
+            .puzzle
+            .square
+            .data
+            .iter()
+            .zip(&p.solution.square.data)
+            .all(|(&a, &b)| a == 0 || a == b));
+    }
+
+    #[test]
+    fn test_puzzle_3() -> Result<(), ParameterSetError> {
+        let mut rng = StdRng::seed_from_u64(3);
+        let p = Construction::<O3>::siamese(1).puzzle(0, &mut rng)?;
+
+        assert_unique(&p);
+        assert!(p.clues() < O3::ELEMENTS);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_puzzle_4() -> Result<(), ParameterSetError> {
+        let square = Construction::<O4>::try_from(
+            [1, 2, 15, 16, 12, 14, 3, 5, 13, 7, 10, 4, 8, 11, 6, 9].as_slice(),
+        )?;
+        let mut rng = StdRng::seed_from_u64(4);
+        let p = square.puzzle(7, &mut rng)?;
+
+        assert_unique(&p);
+        assert!(p.clues() >= 7);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_puzzle_seeded() -> Result<(), ParameterSetError> {
+        let square = Construction::<O5>::siamese(2);
+        let a = square.puzzle(12, &mut StdRng::seed_from_u64(5))?;
+        let b = square.puzzle(12, &mut StdRng::seed_from_u64(5))?;
+
+        assert_eq!(a, b);
+        assert_unique(&a);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_puzzle_6() -> Result<(), ParameterSetError> {
+        let square = Construction::<O6>::try_from(
+            [
+                35, 1, 6, 26, 19, 24, 3, 32, 7, 21, 23, 25, 31, 9, 2, 22, 27, 20, 8, 28, 33, 17,
+                10, 15, 30, 5, 34, 12, 14, 16, 4, 36, 29, 13, 18, 11,
+            ]
+            .as_slice(),
+        )?;
+        let p = square.puzzle(24, &mut StdRng::seed_from_u64(6))?;
+
+        assert_unique(&p);
+        assert!(p.clues() <= 24);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_magic() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(Construction::<O3>::identity().puzzle(4, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_difficulty() {
+        let stats = CompletionStats {
+            propagations: 4,
+            branches: 0,
+            backtracks: 0,
+        };
+        assert_eq!(Difficulty::from_stats(&stats), Difficulty::Easy);
+        assert_eq!(
+            Difficulty::from_stats(&CompletionStats {
+                branches: 9,
+                ..stats
+            }),
+            Difficulty::Hard
+        );
+    }
+}
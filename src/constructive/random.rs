@@ -1,5 +1,5 @@
 use crate::{Construction, Params};
-use rand::{self, seq::SliceRandom};
+use rand::{self, seq::SliceRandom, Rng};
 
 impl<P: Params + Copy> Construction<P>
 where
@@ -8,13 +8,21 @@ where
     /// Shuffles the elements of the construction's square using the Fisher-Yates algorithm.
     pub fn shuffle(&mut self) -> &mut Self {
         let mut rng = rand::thread_rng();
-        self.square.data.shuffle(&mut rng);
+        self.shuffle_with(&mut rng)
+    }
+
+    /// Shuffles the elements of the construction's square using the Fisher-Yates algorithm,
+    /// drawing from the provided random number generator.
+    pub fn shuffle_with<R: Rng>(&mut self, rng: &mut R) -> &mut Self {
+        self.square.data.shuffle(rng);
         self
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
     use crate::O3;
 
     use super::*;
@@ -26,4 +34,14 @@ mod tests {
 
         assert_ne!(a, Construction::<O3>::identity());
     }
+
+    #[test]
+    fn test_shuffle_with() {
+        let mut a = Construction::<O3>::identity();
+        let mut b = Construction::<O3>::identity();
+        a.shuffle_with(&mut StdRng::seed_from_u64(7));
+        b.shuffle_with(&mut StdRng::seed_from_u64(7));
+
+        assert_eq!(a, b);
+    }
 }
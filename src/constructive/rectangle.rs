@@ -0,0 +1,456 @@
+use crate::{MagicRectangle, RectParams};
+
+impl<R: RectParams> MagicRectangle<R> {
+    /// Creates a normal magic rectangle of `R::ROWS` rows and `R::COLS` columns.
+    ///
+    /// A magic rectangle exists exactly when both sides have the same parity and it is neither
+    /// 2×2 nor a single row or column of more than one cell. The construction is chosen by the
+    /// sides, building the rectangle with fewer rows and transposing when needed:
+    ///
+    /// - Even sides: pairs of rows, or pairs of columns, hold distances from the centre under the
+    ///   signs `+ - - +` and their negations, with a fixed 2×6 or 6×6 block taking up the sides
+    ///   which are 2 modulo 4.
+    /// - Odd sides which are equal: the Siamese method.
+    /// - Three odd rows: a shifted middle row, with the top and bottom rows swapped in the columns
+    ///   of a subset sum, and thrice the columns of a smaller rectangle when there is no such
+    ///   subset.
+    /// - More odd rows: three rows whose columns sum to zero about the centre, then pairs of a row
+    ///   and its negation, each splitting the remaining distances into halves of equal sum.
+    ///
+    /// # Panics
+    /// - If no magic rectangle has these sides.
+    #[must_use]
+    pub fn construct() -> Self {
+        let (m, n) = (R::ROWS, R::COLS);
+        assert!(
+            m % 2 == n % 2 && (m, n) != (2, 2) && (m.min(n) > 1 || m * n == 1),
+            "No magic rectangle has these sides!"
+        );
+
+        Self::new(entries(m, n))
+            .unwrap_or_else(|_| unreachable!("one entry is built for every cell"))
+    }
+}
+
+/// Builds the entries of a magic rectangle of `rows` rows and `cols` columns, row by row.
+fn entries(rows: usize, cols: usize) -> Vec<u32> {
+    if rows > cols {
+        return transpose(&entries(cols, rows), cols, rows);
+    }
+
+    match (rows, cols) {
+        (1, _) => vec![1],
+        (m, n) if m % 2 == 0 => even(m, n),
+        (m, n) if m == n => siamese(n),
+        (3, n) => three_rows(n),
+        (m, n) => framework(m, n),
+    }
+}
+
+fn transpose(data: &[u32], rows: usize, cols: usize) -> Vec<u32> {
+    let mut out = vec![0; data.len()];
+    for i in 0..rows {
+        for j in 0..cols {
+            out[j * rows + i] = data[i * cols + j];
+        }
+    }
+
+    out
+}
+
+/// The 6×6 magic square taking up the corner of an even rectangle whose sides are both 2 modulo 4.
+const SIX: [[i64; 6]; 6] = [
+    [35, 1, 6, 26, 19, 24],
+    [3, 32, 7, 21, 23, 25],
+    [31, 9, 2, 22, 27, 20],
+    [8, 28, 33, 17, 10, 15],
+    [30, 5, 34, 12, 14, 16],
+    [4, 36, 29, 13, 18, 11],
+];
+
+/// The signed offsets from the centre of a rectangle, filled in by pairs of lines which cancel.
+struct Offsets {
+    data: Vec<i64>,
+    cols: usize,
+    next: i64,
+}
+
+impl Offsets {
+    /// Fills `rows` rows from `top`, `cols` columns from `left`, with pairs of a row taking the
+    /// next distances under the signs `+ - - +` and the row below it taking their negations.
+    fn row_pairs(&mut self, top: usize, left: usize, rows: usize, cols: usize) {
+        for p in 0..rows / 2 {
+            for j in 0..cols {
+                let e = self.take(j);
+                self.data[(top + 2 * p) * self.cols + left + j] = e;
+                self.data[(top + 2 * p + 1) * self.cols + left + j] = -e;
+            }
+        }
+    }
+
+    /// As `row_pairs`, with the roles of rows and columns exchanged.
+    fn column_pairs(&mut self, top: usize, left: usize, rows: usize, cols: usize) {
+        for p in 0..cols / 2 {
+            for i in 0..rows {
+                let e = self.take(i);
+                self.data[(top + i) * self.cols + left + 2 * p] = e;
+                self.data[(top + i) * self.cols + left + 2 * p + 1] = -e;
+            }
+        }
+    }
+
+    /// Takes the next odd distance, signed by its position `k` along the line.
+    fn take(&mut self, k: usize) -> i64 {
+        let e = self.next;
+        self.next += 2;
+        if matches!(k % 4, 1 | 2) {
+            -e
+        } else {
+            e
+        }
+    }
+}
+
+/// Magic rectangles with even sides, as offsets `e` from the centre with entries `(N + 1 + e) / 2`
+/// so that the distances are the odd numbers below `N`.
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn even(m: usize, n: usize) -> Vec<u32> {
+    let total = (m * n) as i64;
+    let mut grid = Offsets {
+        data: vec![0; m * n],
+        cols: n,
+        next: 1,
+    };
+
+    if n.is_multiple_of(4) {
+        grid.row_pairs(0, 0, m, n);
+    } else if m.is_multiple_of(4) {
+        grid.column_pairs(0, 0, m, n);
+    } else if m == 2 {
+        let base = [[-1, -3, -5, 7, -9, 11], [1, 3, 5, -7, 9, -11]];
+        for (i, row) in base.iter().enumerate() {
+            grid.data[i * n..i * n + 6].copy_from_slice(row);
+        }
+        grid.next = 13;
+        grid.row_pairs(0, 6, 2, n - 6);
+    } else {
+        for (i, row) in SIX.iter().enumerate() {
+            for (j, &v) in row.iter().enumerate() {
+                grid.data[i * n + j] = 2 * v - 37;
+            }
+        }
+        grid.next = 37;
+        grid.column_pairs(6, 0, m - 6, 6);
+        grid.row_pairs(0, 6, m, n - 6);
+    }
+
+    grid.data
+        .iter()
+        .map(|&e| ((total + 1 + e) / 2) as u32)
+        .collect()
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn siamese(n: usize) -> Vec<u32> {
+    let mut data = vec![0; n * n];
+    let (mut i, mut j) = (0, n / 2);
+    for value in 1..=n * n {
+        data[i * n + j] = value as u32;
+        let (up, right) = ((i + n - 1) % n, (j + 1) % n);
+        if data[up * n + right] == 0 {
+            (i, j) = (up, right);
+        } else {
+            i = (i + 1) % n;
+        }
+    }
+
+    data
+}
+
+/// Magic rectangles of three rows and an odd number of columns.
+#[allow(clippy::cast_possible_truncation)]
+fn three_rows(n: usize) -> Vec<u32> {
+    if n == 3 {
+        return siamese(3);
+    }
+
+    let h = (n - 1) / 2;
+    let column = 3 * (3 * n + 1) / 2;
+    let mut top = (1..=n).collect::<Vec<_>>();
+    let middle = (0..n).map(|j| n + 1 + (j + h) % n).collect::<Vec<_>>();
+    let mut bottom = (0..n)
+        .map(|j| column - top[j] - middle[j])
+        .collect::<Vec<_>>();
+
+    let gaps = (0..n).map(|j| bottom[j] - top[j]).collect::<Vec<_>>();
+    if let Some(swaps) = subset_sum(&gaps, n * n) {
+        for j in swaps {
+            std::mem::swap(&mut top[j], &mut bottom[j]);
+        }
+
+        return [top, middle, bottom]
+            .concat()
+            .into_iter()
+            .map(|v| v as u32)
+            .collect();
+    }
+
+    assert!(
+        n.is_multiple_of(3),
+        "Three rows of {n} columns have no subset to swap!"
+    );
+    let k = n / 3;
+    let small = three_rows(k);
+    let mut data = vec![0; 3 * n];
+    for i in 0..3 {
+        for j in 0..k {
+            for u in 0..3 {
+                data[i * n + j * 3 + u] = (small[i * k + j] - 1) * 3 + ((i + u) % 3) as u32 + 1;
+            }
+        }
+    }
+
+    data
+}
+
+/// Magic rectangles of `m >= 5` odd rows and `n > m` odd columns, as offsets from the centre
+/// with distances `1..=(m * n - 1) / 2`.
+///
+/// The top three rows hold a 3×3 core and triples `(x, y, -z)` with `x + y = z` in column pairs
+/// with their negations, so every column of them sums to zero. The remaining distances are split
+/// into groups of `n` which each hold two halves of equal sum, one half signed `+` and the other
+/// `-` along a row, above a row of the opposite signs.
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn framework(m: usize, n: usize) -> Vec<u32> {
+    let (s, t) = ((m - 3) / 2, (n - 3) / 2);
+    let half = (m * n - 1) / 2;
+    let h = half - t - 5;
+
+    let triples = (0..t)
+        .map(|i| [6 + 2 * i, h - i, h + 6 + i])
+        .collect::<Vec<_>>();
+    let rest = half * (half + 1) / 2 - triples.iter().flatten().sum::<usize>();
+    let (a, b) = if (rest - 10).is_multiple_of(2) {
+        (3, 1)
+    } else {
+        (3, 2)
+    };
+    let c = a - b;
+
+    let mut rows = vec![
+        vec![a as i64, -((a + b) as i64), b as i64],
+        vec![-(c as i64), 0, c as i64],
+        vec![-(b as i64), (a + b) as i64, -(a as i64)],
+    ];
+    for &[x, y, z] in &triples {
+        for (row, v) in rows.iter_mut().zip([x as i64, y as i64, -(z as i64)]) {
+            row.extend([v, -v]);
+        }
+    }
+
+    let used = [a, b, c, a + b];
+    let free = (1..=half)
+        .filter(|d| !used.contains(d) && !triples.iter().flatten().any(|x| x == d))
+        .collect::<Vec<_>>();
+
+    let mut groups = vec![Vec::new(); s];
+    for (p, &d) in free.iter().enumerate() {
+        let (r, i) = (p / s, p % s);
+        groups[if r % 2 == 0 { i } else { s - 1 - i }].push(d);
+    }
+
+    let mut odd = (0..s)
+        .filter(|&g| groups[g].iter().sum::<usize>() % 2 != 0)
+        .collect::<Vec<_>>();
+    while let (Some(g1), Some(g2)) = (odd.pop(), odd.pop()) {
+        let (i, k) = (0..groups[g1].len())
+            .flat_map(|i| (0..groups[g2].len()).map(move |k| (i, k)))
+            .find(|&(i, k)| (groups[g1][i] + groups[g2][k]) % 2 != 0)
+            .unwrap_or_else(|| unreachable!("groups of odd sum hold both parities"));
+        swap(&mut groups, (g1, i), (g2, k));
+    }
+
+    assert!(repair(&mut groups), "No groups of {m} by {n} split evenly!");
+
+    for group in &groups {
+        let sum = group.iter().sum::<usize>();
+        let plus = subset_sum(group, sum / 2)
+            .unwrap_or_else(|| unreachable!("every group is repaired to split evenly"));
+        let row = group
+            .iter()
+            .enumerate()
+            .map(|(k, &d)| {
+                if plus.contains(&k) {
+                    d as i64
+                } else {
+                    -(d as i64)
+                }
+            })
+            .collect::<Vec<_>>();
+        let negated = row.iter().map(|&d| -d).collect();
+        rows.extend([row, negated]);
+    }
+
+    let centre = (m * n).div_ceil(2) as i64;
+    rows.into_iter()
+        .flatten()
+        .map(|x| (centre + x) as u32)
+        .collect()
+}
+
+fn swap(groups: &mut [Vec<usize>], (g1, i): (usize, usize), (g2, k): (usize, usize)) {
+    let x = groups[g1][i];
+    groups[g1][i] = groups[g2][k];
+    groups[g2][k] = x;
+}
+
+/// Whether a group splits into two halves of equal sum.
+fn splits(group: &[usize]) -> bool {
+    let sum = group.iter().sum::<usize>();
+    sum % 2 == 0 && subset_sum(group, sum / 2).is_some()
+}
+
+/// Swaps single entries between groups until every group splits evenly, returning whether it
+/// succeeded.
+fn repair(groups: &mut [Vec<usize>]) -> bool {
+    for gi in 0..groups.len() {
+        if splits(&groups[gi]) {
+            continue;
+        }
+
+        let mut found = false;
+        'search: for gj in (0..groups.len()).filter(|&gj| gj != gi) {
+            for i in 0..groups[gi].len() {
+                for k in 0..groups[gj].len() {
+                    swap(groups, (gi, i), (gj, k));
+                    if splits(&groups[gi]) && splits(&groups[gj]) {
+                        found = true;
+                        break 'search;
+                    }
+                    swap(groups, (gi, i), (gj, k));
+                }
+            }
+        }
+
+        if !found {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Finds the indexes of a subset of `items` summing to `target`, using a bitset of the sums
+/// reachable by every prefix.
+fn subset_sum(items: &[usize], target: usize) -> Option<Vec<usize>> {
+    let words = target / 64 + 1;
+    let has = |set: &[u64], s: usize| set[s / 64] >> (s % 64) & 1 == 1;
+
+    let mut reach = vec![vec![0u64; words]];
+    reach[0][0] = 1;
+    for &v in items {
+        let prev = &reach[reach.len() - 1];
+        let mut next = prev.clone();
+        let (shift, bits) = (v / 64, v % 64);
+        for w in (shift..words).rev() {
+            let mut x = prev[w - shift] << bits;
+            if bits > 0 && w > shift {
+                x |= prev[w - shift - 1] >> (64 - bits);
+            }
+            next[w] |= x;
+        }
+        reach.push(next);
+    }
+
+    if !has(&reach[items.len()], target) {
+        return None;
+    }
+
+    let mut chosen = Vec::new();
+    let mut s = target;
+    for k in (0..items.len()).rev() {
+        if !has(&reach[k], s) {
+            chosen.push(k);
+            s -= items[k];
+        }
+    }
+
+    Some(chosen)
+}
+
+#[cfg(test)]
+mod test_rectangle {
+    use itertools::Itertools;
+
+    use crate::{R2x4, R2x6, R3x5, R3x9, R4x6, R5x7, R6x10, R7x9, O4, O5};
+
+    use super::*;
+
+    fn is_magic(data: &[u32], m: usize, n: usize) -> bool {
+        let total = (m * n) as u32;
+        data.iter().copied().sorted().eq(1..=total)
+            && data
+                .chunks(n)
+                .all(|row| row.iter().sum::<u32>() == n as u32 * (total + 1) / 2)
+            && (0..n)
+                .all(|j| data.iter().skip(j).step_by(n).sum::<u32>() == m as u32 * (total + 1) / 2)
+    }
+
+    #[test]
+    fn test_construct() {
+        for (a, m, n) in [
+            (MagicRectangle::<R2x4>::construct().data, 2, 4),
+            (MagicRectangle::<R2x6>::construct().data, 2, 6),
+            (MagicRectangle::<R3x5>::construct().data, 3, 5),
+            (MagicRectangle::<R3x9>::construct().data, 3, 9),
+            (MagicRectangle::<R4x6>::construct().data, 4, 6),
+            (MagicRectangle::<R5x7>::construct().data, 5, 7),
+            (MagicRectangle::<R6x10>::construct().data, 6, 10),
+            (MagicRectangle::<R7x9>::construct().data, 7, 9),
+        ] {
+            assert!(is_magic(&a, m, n), "{m}x{n}");
+        }
+
+        assert!(MagicRectangle::<R5x7>::construct().check_n_s().is_some());
+        assert!(MagicRectangle::<O4>::construct().check_n_s().is_some());
+        assert!(MagicRectangle::<O5>::construct().check_n_s().is_some());
+    }
+
+    #[test]
+    fn test_all_sides() {
+        for m in 2..=17 {
+            for n in (2..=17).filter(|&n| n % 2 == m % 2 && (m, n) != (2, 2)) {
+                assert!(is_magic(&entries(m, n), m, n), "{m}x{n}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "No magic rectangle has these sides!")]
+    fn test_mixed_parity() {
+        #[derive(Debug, Clone, Copy)]
+        struct Mixed;
+        impl RectParams for Mixed {
+            const ROWS: usize = 2;
+            const COLS: usize = 3;
+            const ROW_SUM: u32 = 10;
+            const COL_SUM: u32 = 7;
+        }
+
+        let _ = MagicRectangle::<Mixed>::construct();
+    }
+
+    #[test]
+    fn test_subset_sum() {
+        assert_eq!(subset_sum(&[3, 5, 9, 70], 79), Some(vec![3, 2]));
+        assert_eq!(subset_sum(&[3, 5, 9], 7), None);
+    }
+}
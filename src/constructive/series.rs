@@ -0,0 +1,267 @@
+use std::marker::PhantomData;
+
+use crate::Params;
+
+/// An iterator over the magic series of order `P::ORDER`: the sets of `P::ORDER` distinct
+/// numbers from `1..=P::ELEMENTS` summing to `P::MAGIC_SUM`, the candidates for every row,
+/// column and diagonal of a magic square.
+///
+/// Each series is yielded as a sorted `Vec`, in lexicographic order. A partial series is cut off
+/// as soon as no larger numbers can bring it to the magic sum.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{magic_series, O3};
+///
+/// let mut series = magic_series::<O3>();
+///
+/// assert_eq!(series.next(), Some(vec![1, 5, 9]));
+/// assert_eq!(series.count(), 7);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MagicSeries<P: Params> {
+    chosen: Vec<u32>,
+    sum: u32,
+    candidate: u32,
+    done: bool,
+    phantom: PhantomData<P>,
+}
+
+/// Returns an iterator over the magic series of order `P::ORDER`.
+#[must_use]
+pub fn magic_series<P: Params>() -> MagicSeries<P> {
+    MagicSeries {
+        chosen: Vec::with_capacity(P::ORDER),
+        sum: 0,
+        candidate: 1,
+        done: false,
+        phantom: PhantomData,
+    }
+}
+
+impl<P: Params> MagicSeries<P> {
+    /// Yields each series as a bitset, with bit `k - 1` set for every number `k` in it.
+    ///
+    /// # Panics
+    /// - If `P::ELEMENTS` is greater than 128.
+    pub fn bits(self) -> impl Iterator<Item = u128> {
+        self.map(|series| series_bits(&series))
+    }
+
+    fn pop(&mut self) -> Option<()> {
+        let last = self.chosen.pop()?;
+        self.sum -= last;
+        self.candidate = last + 1;
+        Some(())
+    }
+}
+
+impl<P: Params> Iterator for MagicSeries<P> {
+    type Item = Vec<u32>;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let top = P::ELEMENTS as u64;
+        while !self.done {
+            if self.chosen.len() == P::ORDER {
+                let series = self.chosen.clone();
+                self.pop();
+                return Some(series);
+            }
+
+            let r = (P::ORDER - self.chosen.len()) as u64;
+            let rest = u64::from(P::MAGIC_SUM - self.sum);
+            let v = u64::from(self.candidate);
+            let reachable = if r == 1 {
+                v <= rest && rest <= top
+            } else {
+                r * v + r * (r - 1) / 2 <= rest && rest <= r * top - r * (r - 1) / 2
+            };
+
+            if reachable {
+                let v = if r == 1 { rest as u32 } else { self.candidate };
+                self.chosen.push(v);
+                self.sum += v;
+                self.candidate = v + 1;
+            } else if self.pop().is_none() {
+                self.done = true;
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the bitset of a series, with bit `k - 1` set for every number `k` in it.
+///
+/// # Panics
+/// - If a number is 0 or greater than 128.
+#[must_use]
+pub fn series_bits(series: &[u32]) -> u128 {
+    series.iter().fold(0, |bits, &k| {
+        assert!(
+            (1..=128).contains(&k),
+            "Series must hold numbers in 1..=128!"
+        );
+        bits | 1 << (k - 1)
+    })
+}
+
+/// Counts the magic series of order `P::ORDER` without listing them, by counting the ways to
+/// pick `k` of the first `m` numbers with each sum, for growing `m`.
+#[must_use]
+pub fn count_magic_series<P: Params>() -> u128 {
+    let (n, target) = (P::ORDER, P::MAGIC_SUM as usize);
+    let mut ways = vec![vec![0u128; target + 1]; n + 1];
+    ways[0][0] = 1;
+
+    for m in 1..=P::ELEMENTS.min(target) {
+        for k in (1..=n).rev() {
+            for s in (m..=target).rev() {
+                ways[k][s] += ways[k - 1][s - m];
+            }
+        }
+    }
+
+    ways[n][target]
+}
+
+/// Returns the partitions of `1..=P::ELEMENTS` into `P::ORDER` disjoint magic series, stopping
+/// after `limit` partitions if one is given. The rows of every magic square are one.
+///
+/// Each partition lists its series by their least numbers, and is found by giving the least
+/// number not yet used every disjoint series it is the least of.
+///
+/// # Panics
+/// - If `P::ELEMENTS` is greater than 128.
+#[must_use]
+pub fn magic_series_partitions<P: Params>(limit: Option<usize>) -> Vec<Vec<Vec<u32>>> {
+    let mut found = Vec::new();
+    visit_partitions::<P>(&mut |parts| {
+        found.push(parts.iter().map(|&bits| bits_series(bits)).collect());
+        limit.is_none_or(|l| found.len() < l)
+    });
+
+    found
+}
+
+/// Counts the partitions of `1..=P::ELEMENTS` into `P::ORDER` disjoint magic series.
+///
+/// # Panics
+/// - If `P::ELEMENTS` is greater than 128.
+#[must_use]
+pub fn count_magic_series_partitions<P: Params>() -> u64 {
+    let mut count = 0;
+    visit_partitions::<P>(&mut |_| {
+        count += 1;
+        true
+    });
+
+    count
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn bits_series(mut bits: u128) -> Vec<u32> {
+    let mut series = Vec::new();
+    while bits != 0 {
+        series.push(bits.trailing_zeros() + 1);
+        bits &= bits - 1;
+    }
+
+    series
+}
+
+/// Calls `visit` with every partition into magic series as bitsets, stopping once it returns
+/// `false`.
+fn visit_partitions<P: Params>(visit: &mut impl FnMut(&[u128]) -> bool) {
+    fn search(
+        by_least: &[Vec<u128>],
+        full: u128,
+        used: u128,
+        parts: &mut Vec<u128>,
+        visit: &mut impl FnMut(&[u128]) -> bool,
+    ) -> bool {
+        if used == full {
+            return visit(parts);
+        }
+
+        let least = (!used).trailing_zeros() as usize;
+        for &series in by_least[least].iter().filter(|&&s| s & used == 0) {
+            parts.push(series);
+            let go_on = search(by_least, full, used | series, parts, visit);
+            parts.pop();
+            if !go_on {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    assert!(
+        P::ELEMENTS <= 128,
+        "Series bitsets hold at most 128 numbers!"
+    );
+
+    let mut by_least = vec![Vec::new(); P::ELEMENTS];
+    for bits in magic_series::<P>().bits() {
+        by_least[bits.trailing_zeros() as usize].push(bits);
+    }
+    let full = u128::MAX >> (128 - P::ELEMENTS);
+
+    search(&by_least, full, 0, &mut Vec::new(), visit);
+}
+
+#[cfg(test)]
+mod test_series {
+    use itertools::Itertools;
+
+    use crate::{O10, O3, O4, O5, O6};
+
+    use super::*;
+
+    #[test]
+    fn test_magic_series() {
+        let series = magic_series::<O4>().collect_vec();
+
+        assert_eq!(series.len(), 86);
+        assert_eq!(series[0], vec![1, 2, 15, 16]);
+        assert!(series.iter().all(|s| s.iter().sum::<u32>() == 34));
+        assert!(series.iter().tuple_windows().all(|(a, b)| a < b));
+        assert_eq!(magic_series::<O5>().count(), 1394);
+        assert_eq!(magic_series::<O3>().bits().next(), Some(0b1_0001_0001));
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(count_magic_series::<O3>(), 8);
+        assert_eq!(count_magic_series::<O4>(), 86);
+        assert_eq!(count_magic_series::<O5>(), 1394);
+        assert_eq!(count_magic_series::<O6>(), 32134);
+        assert_eq!(
+            count_magic_series::<O6>(),
+            magic_series::<O6>().count() as u128
+        );
+        assert_eq!(count_magic_series::<O10>(), 78_132_541_528);
+    }
+
+    #[test]
+    fn test_partitions() {
+        assert_eq!(
+            magic_series_partitions::<O3>(None),
+            vec![
+                vec![vec![1, 5, 9], vec![2, 6, 7], vec![3, 4, 8]],
+                vec![vec![1, 6, 8], vec![2, 4, 9], vec![3, 5, 7]],
+            ]
+        );
+        assert_eq!(count_magic_series_partitions::<O4>(), 392);
+        assert_eq!(magic_series_partitions::<O4>(Some(10)).len(), 10);
+    }
+
+    #[test]
+    #[ignore = "visits every partition of order 5"]
+    fn test_partitions_5() {
+        assert_eq!(count_magic_series_partitions::<O5>(), 3_245_664);
+    }
+}
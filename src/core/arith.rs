@@ -0,0 +1,34 @@
+use std::ops::{Rem, Sub};
+
+/// Calculates the greatest common divisor of two integers, which is never negative. The gcd of
+/// `0` and `0` is `0`.
+pub(crate) fn gcd<T>(mut a: T, mut b: T) -> T
+where
+    T: Copy + Default + PartialOrd + Rem<Output = T> + Sub<Output = T>,
+{
+    let zero = T::default();
+    while b != zero {
+        (a, b) = (b, a % b);
+    }
+
+    if a < zero {
+        zero - a
+    } else {
+        a
+    }
+}
+
+#[cfg(test)]
+mod test_arith {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12usize, 18), 6);
+        assert_eq!(gcd(7usize, 0), 7);
+        assert_eq!(gcd(0usize, 0), 0);
+        assert_eq!(gcd(-12i128, 18), 6);
+        assert_eq!(gcd(12i128, -18), 6);
+        assert_eq!(gcd(0i128, -5), 5);
+    }
+}
@@ -1,7 +1,8 @@
 use flexi_logger::{FileSpec, Logger, WriteMode};
 use log::Level;
 
-use crate::{Enumerable, Params, Permutation, O3, O4, O5};
+use crate::{check_batch_v, Enumerable, Params, Permutation, O3, O4, O5};
+use itertools::Itertools;
 use std::{
     collections::BTreeSet,
     fmt::Debug,
@@ -51,6 +52,7 @@ impl NotAssigned for No {}
 ///     - `FirstSet`: shared flag/thread breakout enabled
 ///
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct MessageSolverBuilder<P: Params, T, ThreadsSet, UpperSet, PollingSet, FirstSet>
 where
     ThreadsSet: ToAssign,
@@ -70,6 +72,7 @@ where
     filename: String,
     echo: bool,
     gen_d: bool,
+    canonical: bool,
     phantom: PhantomData<P>,
     t_set_phantom: PhantomData<ThreadsSet>,
     u_set_phantom: PhantomData<UpperSet>,
@@ -104,6 +107,7 @@ where
             path: self.path,
             echo: self.echo,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -131,6 +135,7 @@ where
             path: self.path,
             echo: self.echo,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -158,6 +163,7 @@ where
             path: self.path,
             echo: self.echo,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -179,6 +185,7 @@ where
             echo: self.echo,
             start: self.start,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             stdout_format: self.stdout_format,
             file_format: self.file_format,
             find_first: self.find_first,
@@ -212,6 +219,7 @@ where
             start: self.start,
             echo: self.echo,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -239,6 +247,7 @@ where
             echo,
             file_format: self.file_format,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -266,6 +275,7 @@ where
             echo: self.echo,
             file_format: self.file_format,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -293,6 +303,7 @@ where
             path: self.path,
             echo: self.echo,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -319,6 +330,7 @@ where
             echo: self.echo,
             file_format: self.file_format,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             stdout_format: self.stdout_format,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
@@ -347,6 +359,7 @@ where
             echo: self.echo,
             file_format,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -374,6 +387,7 @@ where
             echo: self.echo,
             file_format: self.file_format,
             gen_d: self.gen_d,
+            canonical: self.canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -401,6 +415,38 @@ where
             path: self.path,
             echo: self.echo,
             gen_d,
+            canonical: self.canonical,
+            phantom: PhantomData {},
+            t_set_phantom: PhantomData {},
+            u_set_phantom: PhantomData {},
+            p_set_phantom: PhantomData {},
+            f_set_phantom: PhantomData {},
+        }
+    }
+
+    /// Only check the permutations in canonical D4 position, skipping the blocks of indexes
+    /// whose corners rule it out. This checks about an eighth of the range; combine it with
+    /// `generate_d(true)` to still log every square of the range's orbits.
+    #[inline]
+    #[must_use]
+    pub fn canonical(
+        self,
+        canonical: bool,
+    ) -> MessageSolverBuilder<P, T, ThreadsSet, UpperSet, PollingSet, FirstSet> {
+        MessageSolverBuilder {
+            threads: self.threads,
+            upper_bound: self.upper_bound,
+            polling_rate: self.polling_rate,
+            find_first: self.find_first,
+            n: self.n,
+            filename: self.filename,
+            stdout_format: self.stdout_format,
+            file_format: self.file_format,
+            start: self.start,
+            path: self.path,
+            echo: self.echo,
+            gen_d: self.gen_d,
+            canonical,
             phantom: PhantomData {},
             t_set_phantom: PhantomData {},
             u_set_phantom: PhantomData {},
@@ -432,6 +478,7 @@ macro_rules! impl_message_solver {
                     stdout_format: OutputFormat::Square,
                     echo: false,
                     gen_d: false,
+                    canonical: false,
                     phantom: PhantomData {},
                     t_set_phantom: PhantomData {},
                     u_set_phantom: PhantomData {},
@@ -484,10 +531,58 @@ pub fn file_logger<S: Into<String>, P: Into<PathBuf>>(
     Ok(Box::new(builder))
 }
 
+/// The number of indexes a solver thread checks with each call to `check_batch_v`.
+const SOLVER_BATCH: usize = 64;
+
+/// Returns the squares of a batch whose bits are set in its mask.
+fn magic_in_batch<P: Params>(
+    batch: &[Permutation<P>],
+    mut mask: u64,
+) -> impl Iterator<Item = Permutation<P>> + '_
+where
+    [(); P::ELEMENTS]:,
+    Permutation<P>: Copy,
+{
+    std::iter::from_fn(move || {
+        let k = mask.trailing_zeros() as usize;
+        mask &= mask.wrapping_sub(1);
+        batch.get(k).copied()
+    })
+}
+
 macro_rules! impl_message_solver_builder {
     ($p:tt, $t:ty) => {
         impl_message_solver!($p, $t);
 
+        impl<ThreadsSet, UpperSet, PollingSet, FirstSet>
+            MessageSolverBuilder<$p, $t, ThreadsSet, UpperSet, PollingSet, FirstSet>
+        where
+            ThreadsSet: ToAssign,
+            UpperSet: ToAssign,
+            PollingSet: ToAssign,
+            FirstSet: ToAssign,
+        {
+            /// Returns the permutations checked by thread `i`. Canonical scans give each thread
+            /// a contiguous part of the range, so that it walks only its own part; otherwise
+            /// thread `i` checks every `threads`-th index of the range from `start + i`.
+            fn thread_permutations(
+                &self,
+                i: usize,
+            ) -> Box<dyn Iterator<Item = Permutation<$p>> + Send> {
+                let (start, threads) = (self.start, self.threads);
+                if self.canonical {
+                    let bound = |i: usize| start + self.upper_bound * i as $t / threads as $t;
+                    Box::new(Permutation::<$p>::canonical_range(bound(i), bound(i + 1)))
+                } else {
+                    Box::new(
+                        (i as $t..self.upper_bound)
+                            .step_by(threads)
+                            .map(move |n| Permutation::<$p>::kth(n + start)),
+                    )
+                }
+            }
+        }
+
         // threads, upper, no poll, no first
         impl MessageSolverBuilder<$p, $t, Yes, Yes, No, No> {
             /// # Errors
@@ -496,16 +591,24 @@ macro_rules! impl_message_solver_builder {
             ///
             #[inline]
             pub fn execute(self) -> Result<(), anyhow::Error> {
-                let logger = file_logger(self.filename, self.path, self.echo)?;
+                let logger = file_logger(self.filename.clone(), self.path.clone(), self.echo)?;
                 logger.start()?;
 
                 let (sx, rx) = mpsc::channel();
 
                 for i in 0..self.threads {
                     let sender: Sender<Permutation<$p>> = sx.clone();
+                    let perms = self.thread_permutations(i);
                     thread::spawn(move || {
-                        for n in (i as $t..self.upper_bound).step_by(self.threads) {
-                            if let Some(sol) = Permutation::<$p>::kth(n + self.start).check_n_s() {
+                        let mut batch = Vec::with_capacity(SOLVER_BATCH);
+                        let mut mask = [0];
+
+                        for chunk in &perms.chunks(SOLVER_BATCH) {
+                            batch.clear();
+                            batch.extend(chunk);
+                            check_batch_v(&batch, &mut mask);
+
+                            for sol in magic_in_batch(&batch, mask[0]) {
                                 match sender.send(sol) {
                                     Ok(()) => {}
                                     Err(_) => {}
@@ -524,7 +627,7 @@ macro_rules! impl_message_solver_builder {
                         Some(idxs) => {
                             if self.gen_d {
                                 let data = idxs
-                                    .generate_d()
+                                    .generate_d_array()
                                     .into_iter()
                                     .collect::<BTreeSet<Permutation<$p>>>();
 
@@ -573,20 +676,32 @@ macro_rules! impl_message_solver_builder {
                 for i in 0..self.threads {
                     let sender: Sender<Permutation<$p>> = sx.clone();
                     let found = f.clone();
+                    let perms = self.thread_permutations(i);
                     thread::spawn(move || {
-                        for (count, n) in (i as $t..self.upper_bound)
-                            .step_by(self.threads)
-                            .enumerate()
-                        {
-                            if let Some(sol) = Permutation::<$p>::kth(n + self.start).check_n_s() {
+                        let mut batch = Vec::with_capacity(SOLVER_BATCH);
+                        let mut mask = [0];
+                        let mut since_poll = 0;
+
+                        for chunk in &perms.chunks(SOLVER_BATCH) {
+                            batch.clear();
+                            batch.extend(chunk);
+                            check_batch_v(&batch, &mut mask);
+
+                            if let Some(sol) = magic_in_batch(&batch, mask[0]).next() {
                                 found.store(self.find_first, Relaxed);
                                 match sender.send(sol) {
                                     Ok(()) => return,
                                     Err(_) => {}
                                 };
-                            } else if count % self.polling_rate == 0 && found.load(Relaxed) {
-                                return;
-                            };
+                            }
+
+                            since_poll += batch.len();
+                            if since_poll >= self.polling_rate {
+                                since_poll = 0;
+                                if found.load(Relaxed) {
+                                    return;
+                                }
+                            }
                         }
                         return;
                     });
@@ -598,7 +713,7 @@ macro_rules! impl_message_solver_builder {
                     Ok(idxs) => {
                         if self.gen_d {
                             let data = idxs
-                                .generate_d()
+                                .generate_d_array()
                                 .into_iter()
                                 .collect::<BTreeSet<Permutation<$p>>>();
 
@@ -630,9 +745,9 @@ impl_message_solver_builder!(O5, u128);
 
 #[cfg(test)]
 mod channels_tests {
-    // use super::*;
+    use super::*;
 
-    use crate::{IndexConst, MessageSolver, O3};
+    use crate::{IndexConst, MagicChecker, MessageSolver, O3};
 
     #[test]
     fn test_builder() -> Result<(), anyhow::Error> {
@@ -645,4 +760,56 @@ mod channels_tests {
             .filename("TestMPSC");
         Ok(())
     }
+
+    #[test]
+    fn test_canonical_split() {
+        // Squares starting 0, 1, 2, 9 have a canonical top-right corner, but only some of
+        // their bottom-left corners are canonical.
+        let builder = |threads| {
+            MessageSolver::<O4>::default_build()
+                .threads(threads)
+                .upper_bound(500_000)
+                .start(6 * 479_001_600 + 12_345)
+                .canonical(true)
+        };
+        let scan = |threads| {
+            (0..threads)
+                .flat_map(|i| builder(threads).thread_permutations(i))
+                .map(|p| p.index())
+                .collect::<Vec<_>>()
+        };
+
+        let single = scan(1);
+        assert!(!single.is_empty() && single.len() < 500_000);
+        assert!(single
+            .iter()
+            .all(|k| (6 * 479_001_600 + 12_345..6 * 479_001_600 + 512_345).contains(k)));
+        for threads in [3, 7] {
+            assert_eq!(scan(threads), single);
+        }
+    }
+
+    #[test]
+    fn test_canonical_threads() {
+        let builder = MessageSolver::<O3>::default_build()
+            .threads(3)
+            .upper_bound(O3::MAX_INDEX)
+            .canonical(true);
+
+        let solutions = (0..3)
+            .flat_map(|i| builder.thread_permutations(i))
+            .filter_map(|p| p.check())
+            .collect::<Vec<_>>();
+        assert_eq!(solutions.len(), 1);
+
+        let orbit = solutions[0]
+            .generate_d_array()
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let all = (0..O3::MAX_INDEX)
+            .map(Permutation::<O3>::kth)
+            .filter_map(|p| p.check())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(orbit, all);
+    }
 }
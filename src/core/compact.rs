@@ -0,0 +1,212 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::{Cell, Enumerable, ParameterSetError, Params, Permutation, Square, O3, O4, O5};
+
+/// A permutation storing its entries in the smallest cell of its order, `P::Cell`.
+///
+/// An order 4 `Permutation` holds 64 bytes of `u32` entries where a `CompactPermutation` holds
+/// 16 bytes of `u8`, which makes large sets of squares a quarter of the size. Both forms hold
+/// the same entries and convert into each other without loss.
+///
+/// The set tools of `prelude` keep taking and returning `Permutation`, and store their order 4
+/// sets internally as the even smaller `PackedPerm16`. Hold a large set of any order in this form
+/// and convert at the edges.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{CompactPermutation, Permutation, O4};
+///
+/// let a = CompactPermutation::<O4>::identity();
+/// assert_eq!(Permutation::from(a), Permutation::<O4>::identity());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct CompactPermutation<P: Params>
+where
+    [(); P::ELEMENTS]:,
+{
+    pub data: [P::Cell; P::ELEMENTS],
+}
+
+impl<P: Params + Copy> CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Returns the identity permutation.
+    ///
+    /// # Panics
+    /// - If `P::Cell` cannot hold `P::ELEMENTS`, which no parameter set allows.
+    #[must_use]
+    pub fn identity() -> Self {
+        Self {
+            data: std::array::from_fn(|i| P::Cell::from_u32(i as u32 + 1).unwrap()),
+        }
+    }
+
+    /// Returns the entries widened to `u32`.
+    #[must_use]
+    pub fn to_array(&self) -> [u32; P::ELEMENTS] {
+        self.data.map(Cell::to_u32)
+    }
+}
+
+impl<P: Params> Hash for CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+    }
+}
+
+impl<P: Params> PartialEq for CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<P: Params> Eq for CompactPermutation<P> where [(); P::ELEMENTS]: {}
+
+impl<P: Params + Copy> fmt::Display for CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Permutation::from(*self))
+    }
+}
+
+impl<P: Params + Copy> From<CompactPermutation<P>> for Permutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn from(c: CompactPermutation<P>) -> Self {
+        Square::<P>::from_array(c.to_array()).to_perm()
+    }
+}
+
+impl<P: Params> TryFrom<Permutation<P>> for CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    type Error = ParameterSetError;
+
+    fn try_from(p: Permutation<P>) -> Result<Self, Self::Error> {
+        CompactPermutation::try_from(p.square.data.as_slice())
+    }
+}
+
+impl<P: Params> TryFrom<&[u32]> for CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    type Error = ParameterSetError;
+
+    fn try_from(slice: &[u32]) -> Result<Self, Self::Error> {
+        if slice.len() != P::ELEMENTS {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match P::ELEMENTS!".to_string(),
+            ));
+        }
+
+        let data = std::array::try_from_fn(|i| P::Cell::from_u32(slice[i]));
+        data.map(|data| Self { data })
+            .ok_or(ParameterSetError::InvalidElements(
+                "Element does not fit in P::Cell!".to_string(),
+            ))
+    }
+}
+
+// Allow set-like types to order compact permutations in the same order as `Permutation`, by
+// index. The Enumerable Params are hard-coded via macro for the same reason.
+//-------------------------------------------------------------------------------------------------
+
+macro_rules! impl_ord_for_enumerable_params {
+    ($p:tt) => {
+        impl PartialOrd for CompactPermutation<$p> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for CompactPermutation<$p> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.index().cmp(&other.index())
+            }
+        }
+    };
+}
+
+impl_ord_for_enumerable_params!(O3);
+impl_ord_for_enumerable_params!(O4);
+impl_ord_for_enumerable_params!(O5);
+
+//-------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test_compact {
+    use std::collections::BTreeSet;
+
+    use crate::{IndexConst, O16, O20, O301};
+
+    use super::*;
+
+    #[test]
+    fn test_size() {
+        assert_eq!(size_of::<CompactPermutation<O4>>(), 16);
+        assert_eq!(size_of::<Permutation<O4>>(), 64);
+        assert_eq!(size_of::<CompactPermutation<O16>>(), 512);
+        assert_eq!(size_of::<CompactPermutation<O20>>(), 800);
+        assert_eq!(
+            size_of::<CompactPermutation<O301>>(),
+            size_of::<Permutation<O301>>()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<(), ParameterSetError> {
+        for k in (0..O4::MAX_INDEX).step_by(1_000_000_007) {
+            let p = Permutation::<O4>::kth(k);
+            let c = CompactPermutation::try_from(p)?;
+            assert_eq!(Permutation::from(c), p);
+            assert_eq!(CompactPermutation::<O4>::kth(k), c);
+            assert_eq!(c.index(), k);
+        }
+
+        assert_eq!(
+            Permutation::from(CompactPermutation::<O16>::identity()),
+            Permutation::<O16>::identity()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut data = [1; 9];
+        data[4] = 256;
+        assert!(matches!(
+            CompactPermutation::<O3>::try_from(data.as_slice()),
+            Err(ParameterSetError::InvalidElements(_))
+        ));
+        assert!(matches!(
+            CompactPermutation::<O3>::try_from([1, 2, 3].as_slice()),
+            Err(ParameterSetError::ElementCount(_))
+        ));
+    }
+
+    #[test]
+    fn test_ord() {
+        let perms = [5, 362_879, 0, 400].map(Permutation::<O3>::kth);
+        let compact = perms.map(|p| CompactPermutation::try_from(p).unwrap());
+
+        let a = perms.into_iter().collect::<BTreeSet<_>>();
+        let b = compact.into_iter().collect::<BTreeSet<_>>();
+        assert!(a.into_iter().eq(b.into_iter().map(Permutation::from)));
+    }
+}
@@ -1,10 +1,19 @@
-use crate::{Params, Permutation, O3, O4, O5};
+use crate::{CompactPermutation, Params, Permutation, O3, O4, O5};
 
-pub trait EnumerableMarker {}
+pub trait EnumerableMarker {
+    /// The integer type indexing the permutations of the order.
+    type Index: Copy + Ord + Send + Sync + std::fmt::Debug;
+}
 
-impl EnumerableMarker for O3 {}
-impl EnumerableMarker for O4 {}
-impl EnumerableMarker for O5 {}
+impl EnumerableMarker for O3 {
+    type Index = u32;
+}
+impl EnumerableMarker for O4 {
+    type Index = u64;
+}
+impl EnumerableMarker for O5 {
+    type Index = u128;
+}
 
 pub trait IndexConst<T> {
     const MAX_INDEX: T;
@@ -29,14 +38,14 @@ where
     [(); P::ELEMENTS]:,
 {
     /// Generate the k-th permutation of the enumerable set.
-    fn kth(k: T) -> Permutation<P>;
-    /// Get the index associated with the current `Permutation`.
+    fn kth(k: T) -> Self;
+    /// Get the index associated with the current permutation.
     fn index(&self) -> T;
 }
 
 macro_rules! impl_fns_for_enumerable_params {
-    ($p:tt, $t:ty) => {
-        impl Enumerable<$p, $t> for Permutation<$p> {
+    ($type:tt, $p:tt, $t:ty, $($data:ident).+) => {
+        impl Enumerable<$p, $t> for $type<$p> {
             fn kth(k: $t) -> Self {
                 let mut n = Self::identity();
                 let mut indeces = [0; $p::ELEMENTS];
@@ -52,13 +61,13 @@ macro_rules! impl_fns_for_enumerable_params {
                 for (i, item) in indeces.iter().enumerate() {
                     let index = item + i as $t;
                     if index != i as $t {
-                        let temp = n.square.data[index as usize];
+                        let temp = n.$($data).+[index as usize];
                         let mut j = index as usize;
                         while j > i {
-                            n.square.data[j] = n.square.data[j - 1];
+                            n.$($data).+[j] = n.$($data).+[j - 1];
                             j -= 1;
                         }
-                        n.square.data[i] = temp;
+                        n.$($data).+[i] = temp;
                     }
                 }
                 n
@@ -71,7 +80,7 @@ macro_rules! impl_fns_for_enumerable_params {
                 for p in (0..=$p::ELEMENTS - 2).rev() {
                     let mut succ = 0;
                     for q in p + 1..$p::ELEMENTS {
-                        if self.square.data[p] > self.square.data[q] {
+                        if self.$($data).+[p] > self.$($data).+[q] {
                             succ += 1;
                         }
                     }
@@ -86,9 +95,12 @@ macro_rules! impl_fns_for_enumerable_params {
     };
 }
 
-impl_fns_for_enumerable_params!(O3, u32);
-impl_fns_for_enumerable_params!(O4, u64);
-impl_fns_for_enumerable_params!(O5, u128);
+impl_fns_for_enumerable_params!(Permutation, O3, u32, square.data);
+impl_fns_for_enumerable_params!(Permutation, O4, u64, square.data);
+impl_fns_for_enumerable_params!(Permutation, O5, u128, square.data);
+impl_fns_for_enumerable_params!(CompactPermutation, O3, u32, data);
+impl_fns_for_enumerable_params!(CompactPermutation, O4, u64, data);
+impl_fns_for_enumerable_params!(CompactPermutation, O5, u128, data);
 
 #[cfg(test)]
 mod test_enumerable {
@@ -3,4 +3,5 @@
 pub enum ParameterSetError {
     InvalidOrder(String),
     ElementCount(String),
+    InvalidElements(String),
 }
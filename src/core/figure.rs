@@ -0,0 +1,204 @@
+use itertools::Itertools;
+
+use crate::{constraint_lines, ParameterSetError, Params};
+
+/// A magic figure: a number of cells and the lines, sets of cell indexes, whose entries must
+/// share a sum.
+///
+/// Squares, stars, hexagons, triangles and graph labellings differ only in their lines, so the
+/// checker and `FigureSolver` run on any of them.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::MagicFigure;
+///
+/// let star = MagicFigure::hexagram();
+/// let a = [1, 3, 2, 4, 9, 5, 11, 8, 7, 10, 6, 12];
+///
+/// assert_eq!(star.check_s(&a), Some(26));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MagicFigure {
+    cells: usize,
+    lines: Vec<Vec<usize>>,
+}
+
+impl MagicFigure {
+    /// Creates a figure from its number of cells and its lines.
+    ///
+    /// # Errors
+    /// - If a line is empty, repeats a cell, or holds an index out of range.
+    pub fn new(cells: usize, lines: Vec<Vec<usize>>) -> Result<Self, ParameterSetError> {
+        for line in &lines {
+            if line.is_empty() || !line.iter().all_unique() || line.iter().any(|&c| c >= cells) {
+                return Err(ParameterSetError::InvalidElements(
+                    "Lines must hold distinct cells of the figure!".to_string(),
+                ));
+            }
+        }
+
+        Ok(Self { cells, lines })
+    }
+
+    /// Returns the number of cells.
+    #[must_use]
+    pub fn cells(&self) -> usize {
+        self.cells
+    }
+
+    /// Returns the lines.
+    #[must_use]
+    pub fn lines(&self) -> &[Vec<usize>] {
+        &self.lines
+    }
+
+    /// Creates the figure of a square of order `P::ORDER`, with lines in the order of its
+    /// constraint vectors: the rows, the columns, then both diagonals.
+    #[must_use]
+    pub fn square<P: Params>() -> Self {
+        Self {
+            cells: P::ELEMENTS,
+            lines: constraint_lines::<P>(),
+        }
+    }
+
+    /// Creates the `points`-pointed magic star, the star polygon joining every point to the
+    /// point two along.
+    ///
+    /// Cells `0..points` are the tips and cell `points + i` is where the line leaving tip `i`
+    /// crosses the line leaving tip `i + 1`. Each of the `points` lines holds two tips and the
+    /// two crossings between them, in order along the line.
+    ///
+    /// # Panics
+    /// - If `points` is less than 5.
+    #[must_use]
+    pub fn star(points: usize) -> Self {
+        assert!(points >= 5, "A magic star has at least 5 points!");
+
+        let n = points;
+        let lines = (0..n)
+            .map(|i| vec![i, n + (i + n - 1) % n, n + i, (i + 2) % n])
+            .collect();
+
+        Self {
+            cells: 2 * n,
+            lines,
+        }
+    }
+
+    /// Creates the 5-pointed magic star.
+    #[must_use]
+    pub fn pentagram() -> Self {
+        Self::star(5)
+    }
+
+    /// Creates the 6-pointed magic star.
+    #[must_use]
+    pub fn hexagram() -> Self {
+        Self::star(6)
+    }
+
+    /// Creates the figure of an edge-magic total labelling of a graph, where the two ends of
+    /// every edge and the edge itself share a sum.
+    ///
+    /// Cells `0..vertices` are the vertices and cell `vertices + e` is edge `e`.
+    ///
+    /// # Errors
+    /// - If an edge is a loop or has an end out of range.
+    pub fn edge_magic(
+        vertices: usize,
+        edges: &[(usize, usize)],
+    ) -> Result<Self, ParameterSetError> {
+        let lines = edges
+            .iter()
+            .enumerate()
+            .map(|(e, &(u, v))| vec![u, v, vertices + e])
+            .collect();
+
+        Self::new(vertices + edges.len(), lines)
+    }
+
+    /// Returns the number of lines through every cell, if it is the same for all cells.
+    #[must_use]
+    pub fn lines_per_cell(&self) -> Option<usize> {
+        let mut counts = vec![0; self.cells];
+        for &c in self.lines.iter().flatten() {
+            counts[c] += 1;
+        }
+
+        counts.into_iter().all_equal_value().ok()
+    }
+
+    /// Returns the only sum a set of entries admits on a figure where every cell lies on the
+    /// same number of lines: that number times the total, divided by the number of lines.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match the number of cells.
+    /// - If the cells lie on differing numbers of lines, so the sum is not determined.
+    /// - If the sum is not an integer.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn magic_sum_of(&self, entries: &[i64]) -> Result<i64, ParameterSetError> {
+        if entries.len() != self.cells {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match the number of cells!".to_string(),
+            ));
+        }
+        let Some(r) = self.lines_per_cell() else {
+            return Err(ParameterSetError::InvalidElements(
+                "Cells lie on differing numbers of lines!".to_string(),
+            ));
+        };
+
+        let weighted = r as i64 * entries.iter().sum::<i64>();
+        let lines = self.lines.len() as i64;
+        if lines == 0 || weighted % lines != 0 {
+            return Err(ParameterSetError::InvalidElements(
+                "Entries admit no integer magic sum!".to_string(),
+            ));
+        }
+
+        Ok(weighted / lines)
+    }
+}
+
+#[cfg(test)]
+mod test_figure {
+    use crate::{O3, O4};
+
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert!(MagicFigure::new(3, vec![vec![0, 1, 2]]).is_ok());
+        assert!(MagicFigure::new(3, vec![vec![0, 1, 3]]).is_err());
+        assert!(MagicFigure::new(3, vec![vec![0, 0]]).is_err());
+        assert!(MagicFigure::new(3, vec![vec![]]).is_err());
+        assert!(MagicFigure::edge_magic(2, &[(0, 2)]).is_err());
+    }
+
+    #[test]
+    fn test_square() {
+        let a = MagicFigure::square::<O4>();
+
+        assert_eq!(a.cells(), 16);
+        assert_eq!(a.lines().len(), O4::CONSTRAINT_VECTORS);
+        assert_eq!(a.lines()[9], vec![3, 6, 9, 12]);
+        assert_eq!(a.lines_per_cell(), None);
+        assert!(MagicFigure::square::<O3>().magic_sum_of(&[1; 9]).is_err());
+    }
+
+    #[test]
+    fn test_star() {
+        let a = MagicFigure::pentagram();
+
+        assert_eq!(a.cells(), 10);
+        assert_eq!(a.lines()[0], vec![0, 9, 5, 2]);
+        assert_eq!(a.lines_per_cell(), Some(2));
+        assert_eq!(a.magic_sum_of(&(1..=10).collect_vec()), Ok(22));
+
+        let b = MagicFigure::hexagram();
+        assert_eq!(b.magic_sum_of(&(1..=12).collect_vec()), Ok(26));
+        assert!(b.magic_sum_of(&(1..=10).collect_vec()).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use itertools::Itertools;
+
+use crate::{Construction, HyperParams, ParameterSetError, Params};
+
+/// A `D`-dimensional hypercube of order `P::ORDER`, stored with the first coordinate varying
+/// fastest.
+///
+/// A cell at coordinates `[x_0, x_1, ..., x_{D-1}]` lies at index `x_0 + x_1 * n + x_2 * n^2 ...`,
+/// so that for `D = 2` the layout is the row-major layout of a square, with `x_0` the column and
+/// `x_1` the row.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{Hypercube, O3};
+///
+/// let a = Hypercube::<O3, 3>::siamese();
+///
+/// assert!(a.check_n_s().is_some());
+/// assert!(a.check_diagonal_n_s().is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hypercube<P: Params, const D: usize> {
+    pub data: Vec<u32>,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params, const D: usize> Hypercube<P, D> {
+    /// Creates a hypercube from its entries.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match `P::ORDER ^ D`.
+    pub fn new(data: Vec<u32>) -> Result<Self, ParameterSetError> {
+        if data.len() != <P as HyperParams<D>>::CELLS {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match P::ORDER ^ D!".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            data,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns the index of the cell at `coords`.
+    #[must_use]
+    pub fn index(coords: &[usize; D]) -> usize {
+        coords.iter().rev().fold(0, |acc, &x| acc * P::ORDER + x)
+    }
+
+    /// Returns the coordinates of the cell at `index`.
+    #[must_use]
+    pub fn coords(mut index: usize) -> [usize; D] {
+        std::array::from_fn(|_| {
+            let x = index % P::ORDER;
+            index /= P::ORDER;
+            x
+        })
+    }
+
+    /// Returns every axis-parallel line: the rows, columns and pillars of a cube.
+    #[must_use]
+    pub fn axis_lines() -> Vec<Vec<usize>> {
+        let n = P::ORDER;
+        (0..D)
+            .flat_map(|axis| {
+                let stride = n.pow(axis as u32);
+                (0..<P as HyperParams<D>>::CELLS)
+                    .filter(move |&c| Self::coords(c)[axis] == 0)
+                    .map(move |c| (0..n).map(|j| c + j * stride).collect())
+            })
+            .collect()
+    }
+
+    /// Returns the `2 ^ (D - 1)` main diagonals joining opposite corners of the hypercube, the
+    /// space diagonals of a cube.
+    #[must_use]
+    pub fn space_diagonals() -> Vec<Vec<usize>> {
+        Self::diagonals(D..=D)
+    }
+
+    /// Returns the main diagonals of every axis-aligned section of two or more dimensions. For a
+    /// cube these are the `6 * n` diagonals of its orthogonal planes and its 4 space diagonals.
+    #[must_use]
+    pub fn section_diagonals() -> Vec<Vec<usize>> {
+        Self::diagonals(2..=D)
+    }
+
+    /// Returns every line, broken or not, stepping by `-1`, `0` or `1` along each axis and
+    /// wrapping around the edges: the axis-parallel lines together with every pan-diagonal.
+    #[must_use]
+    pub fn pan_lines() -> Vec<Vec<usize>> {
+        let n = P::ORDER;
+        let directions = (0..D)
+            .map(|_| [0, 1, n - 1])
+            .multi_cartesian_product()
+            .filter(|d| d.iter().find(|&&s| s != 0) == Some(&1));
+
+        directions
+            .flat_map(|d| {
+                let lead = d.iter().position(|&s| s != 0).unwrap_or_default();
+                (0..<P as HyperParams<D>>::CELLS)
+                    .filter(move |&c| Self::coords(c)[lead] == 0)
+                    .map(move |c| {
+                        let start = Self::coords(c);
+                        (0..n)
+                            .map(|j| {
+                                let x = std::array::from_fn(|k| (start[k] + j * d[k]) % n);
+                                Self::index(&x)
+                            })
+                            .collect()
+                    })
+                    .collect_vec()
+            })
+            .collect()
+    }
+
+    /// Returns the lines of a simple magic hypercube, adding the diagonals of every section
+    /// when `sections` is set.
+    #[must_use]
+    pub fn constraint_lines(sections: bool) -> Vec<Vec<usize>> {
+        let diagonals = if sections {
+            Self::section_diagonals()
+        } else {
+            Self::space_diagonals()
+        };
+
+        Self::axis_lines().into_iter().chain(diagonals).collect()
+    }
+
+    /// Returns the main diagonals of every axis-aligned section whose dimension lies in `dims`.
+    fn diagonals(dims: std::ops::RangeInclusive<usize>) -> Vec<Vec<usize>> {
+        let n = P::ORDER;
+        let mut lines = vec![];
+
+        for k in dims {
+            for axes in (0..D).combinations(k) {
+                let fixed = (0..<P as HyperParams<D>>::CELLS)
+                    .filter(|&c| axes.iter().all(|&a| Self::coords(c)[a] == 0));
+
+                for base in fixed {
+                    let base = Self::coords(base);
+                    // The first axis always ascends, so each diagonal is listed once.
+                    for signs in 0..1usize << (k - 1) {
+                        let line = (0..n).map(|j| {
+                            let mut x = base;
+                            for (s, &a) in axes.iter().enumerate() {
+                                let descending = s > 0 && signs >> (s - 1) & 1 == 1;
+                                x[a] = if descending { n - 1 - j } else { j };
+                            }
+                            Self::index(&x)
+                        });
+                        lines.push(line.collect());
+                    }
+                }
+            }
+        }
+
+        lines
+    }
+}
+
+impl<P: Params, const D: usize> TryFrom<&[u32]> for Hypercube<P, D> {
+    type Error = ParameterSetError;
+
+    fn try_from(item: &[u32]) -> Result<Self, Self::Error> {
+        Self::new(item.to_vec())
+    }
+}
+
+impl<P: Params> From<&Construction<P>> for Hypercube<P, 2>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn from(item: &Construction<P>) -> Self {
+        Self {
+            data: item.square.data.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: Params, const D: usize> fmt::Display for Hypercube<P, D> {
+    /// Writes each square section `x_2, x_3, ...` in turn, separated by blank lines.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.data.chunks(P::ORDER).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+                if i % P::ORDER == 0 {
+                    writeln!(f)?;
+                }
+            }
+            write!(f, "{row:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_hypercube {
+    use crate::{O3, O4, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_coords() {
+        assert_eq!(Hypercube::<O3, 3>::coords(5), [2, 1, 0]);
+        assert_eq!(Hypercube::<O3, 3>::index(&[2, 1, 0]), 5);
+        assert!((0..81).all(|i| Hypercube::<O3, 4>::index(&Hypercube::<O3, 4>::coords(i)) == i));
+    }
+
+    #[test]
+    fn test_line_counts() {
+        assert_eq!(Hypercube::<O4, 3>::axis_lines().len(), 3 * 16);
+        assert_eq!(Hypercube::<O4, 3>::space_diagonals().len(), 4);
+        assert_eq!(Hypercube::<O4, 3>::section_diagonals().len(), 6 * 4 + 4);
+        assert_eq!(Hypercube::<O5, 3>::pan_lines().len(), 13 * 25);
+        assert_eq!(
+            Hypercube::<O3, 4>::constraint_lines(false).len(),
+            <O3 as HyperParams<4>>::LINES
+        );
+
+        let square = Hypercube::<O5, 2>::constraint_lines(true);
+        assert_eq!(square.len(), O5::CONSTRAINT_VECTORS);
+        assert_eq!(square[10], vec![0, 6, 12, 18, 24]);
+        assert_eq!(square[11], vec![20, 16, 12, 8, 4]);
+    }
+
+    #[test]
+    fn test_new() {
+        assert!(Hypercube::<O3, 3>::new(vec![0; 27]).is_ok());
+        assert!(Hypercube::<O3, 3>::new(vec![0; 9]).is_err());
+        assert_eq!(<O3 as HyperParams<3>>::LINE_SUM, 42);
+    }
+}
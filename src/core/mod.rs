@@ -1,14 +1,32 @@
+mod arith;
 mod channels;
+mod compact;
 mod enumerable;
 mod error;
+mod figure;
 mod file;
+mod hypercube;
+mod multiplicative;
+mod non_normal;
+mod packed;
 mod perms;
+mod range;
+mod rectangle;
 mod square;
 mod transform;
 
+pub(crate) use arith::*;
 pub use channels::*;
+pub use compact::*;
 pub use enumerable::*;
 pub use error::*;
+pub use figure::*;
 pub use file::*;
+pub use hypercube::*;
+pub use multiplicative::*;
+pub use non_normal::*;
+pub use packed::*;
 pub use perms::*;
+pub use range::*;
+pub use rectangle::*;
 pub use square::*;
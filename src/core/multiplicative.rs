@@ -0,0 +1,145 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use itertools::Itertools;
+
+use crate::{Construction, ParameterSetError, Params};
+
+/// A square whose rows, columns and diagonals must share a common product.
+///
+/// Entries are stored as `u128`, and every product is computed with checked arithmetic. A square
+/// with a line product overflowing a `u128` is reported as not magic, rather than wrapping into a
+/// false positive.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{MultiplicativeSquare, O3};
+///
+/// let a = MultiplicativeSquare::<O3>::try_from([2, 9, 12, 36, 6, 1, 3, 4, 18].as_slice()).unwrap();
+///
+/// assert_eq!(a.magic_product(), Some(216));
+/// assert!(a.check_n_s().is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MultiplicativeSquare<P: Params> {
+    pub data: Vec<u128>,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params> MultiplicativeSquare<P> {
+    /// Creates a square from its entries.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match `P::ELEMENTS`.
+    /// - If an entry is zero.
+    pub fn new(data: Vec<u128>) -> Result<Self, ParameterSetError> {
+        if data.len() != P::ELEMENTS {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match P::ELEMENTS!".to_string(),
+            ));
+        }
+        if data.contains(&0) {
+            return Err(ParameterSetError::InvalidElements(
+                "Entries of a multiplicative square must be positive!".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            data,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns the product of the first row, or `None` if it overflows.
+    #[must_use]
+    pub fn magic_product(&self) -> Option<u128> {
+        self.data[..P::ORDER]
+            .iter()
+            .try_fold(1u128, |acc, &x| acc.checked_mul(x))
+    }
+
+    /// Returns the largest entry.
+    #[must_use]
+    pub fn max_entry(&self) -> u128 {
+        self.data.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Returns `true` if no entry is repeated.
+    #[must_use]
+    pub fn is_distinct(&self) -> bool {
+        self.data.iter().all_unique()
+    }
+
+    /// Creates a square by raising `base` to the power `x - 1` for every entry `x` of an
+    /// additive construction, so that the smallest entry is one. A magic construction becomes a
+    /// multiplicative magic square with product `base ^ (P::MAGIC_SUM - P::ORDER)`.
+    ///
+    /// Returns `None` if an entry overflows a `u128`.
+    #[must_use]
+    pub fn exponentiate(construction: &Construction<P>, base: u128) -> Option<Self>
+    where
+        [(); P::ELEMENTS]:,
+    {
+        let data = construction
+            .square
+            .data
+            .iter()
+            .map(|&x| base.checked_pow(x.checked_sub(1)?))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            data,
+            phantom: PhantomData,
+        })
+    }
+}
+
+impl<P: Params> TryFrom<&[u128]> for MultiplicativeSquare<P> {
+    type Error = ParameterSetError;
+
+    fn try_from(item: &[u128]) -> Result<Self, Self::Error> {
+        Self::new(item.to_vec())
+    }
+}
+
+impl<P: Params> fmt::Display for MultiplicativeSquare<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut a = self.data.iter();
+        for i in 1..=P::ORDER {
+            write!(f, "{:?}", a.by_ref().take(P::ORDER).collect_vec())?;
+            if i != P::ORDER {
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_multiplicative {
+    use crate::{O3, O4};
+
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert!(MultiplicativeSquare::<O3>::new(vec![1; 9]).is_ok());
+        assert!(MultiplicativeSquare::<O3>::new(vec![1; 8]).is_err());
+        assert!(MultiplicativeSquare::<O3>::new(vec![1, 1, 1, 1, 0, 1, 1, 1, 1]).is_err());
+    }
+
+    #[test]
+    fn test_exponentiate() {
+        let c = Construction::<O3>::siamese(1);
+        let a = MultiplicativeSquare::exponentiate(&c, 2).unwrap();
+
+        assert_eq!(a.magic_product(), Some(1 << 12));
+        assert_eq!(a.max_entry(), 256);
+        assert!(a.is_distinct());
+
+        let b = MultiplicativeSquare::<O4>::exponentiate(&Construction::identity(), 1000);
+        assert_eq!(b, None);
+    }
+}
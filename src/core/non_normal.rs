@@ -0,0 +1,201 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use itertools::Itertools;
+
+use crate::{Construction, ParameterSetError, Params, O3};
+
+/// A square of arbitrary integer entries whose magic constant is runtime data.
+///
+/// `Params::MAGIC_SUM` only describes squares of `1..=P::ELEMENTS`. A `NonNormalSquare<P>` holds
+/// any entries, repeated or negative, together with the sum its constraint vectors must share,
+/// so that squares of primes, of consecutive integers starting elsewhere, or with a chosen
+/// constant can be described and checked.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{NonNormalSquare, O3};
+///
+/// let a = NonNormalSquare::<O3>::from_entries(vec![17, 89, 71, 113, 59, 5, 47, 29, 101]).unwrap();
+///
+/// assert_eq!(a.magic_sum, 177);
+/// assert!(a.check_n_s().is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NonNormalSquare<P: Params> {
+    pub data: Vec<i64>,
+    pub magic_sum: i64,
+    phantom: PhantomData<P>,
+}
+
+impl<P: Params> NonNormalSquare<P> {
+    /// Creates a square from its entries and the sum its constraint vectors must share.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match `P::ELEMENTS`.
+    pub fn new(data: Vec<i64>, magic_sum: i64) -> Result<Self, ParameterSetError> {
+        if data.len() != P::ELEMENTS {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match P::ELEMENTS!".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            data,
+            magic_sum,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Creates a square from its entries, taking the only magic constant they admit: their
+    /// total divided by the order.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match `P::ELEMENTS`.
+    /// - If the total of the entries is not divisible by `P::ORDER`.
+    pub fn from_entries(data: Vec<i64>) -> Result<Self, ParameterSetError> {
+        let magic_sum = magic_sum_of::<P>(&data)?;
+        Self::new(data, magic_sum)
+    }
+
+    /// Returns the entries in ascending order.
+    #[must_use]
+    pub fn entries(&self) -> Vec<i64> {
+        self.data.iter().copied().sorted_unstable().collect()
+    }
+
+    /// Returns `true` if the entries are exactly `1..=P::ELEMENTS`.
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn is_normal(&self) -> bool {
+        self.entries().into_iter().eq(1..=P::ELEMENTS as i64)
+    }
+
+    /// Returns `true` if no entry is repeated.
+    #[must_use]
+    pub fn is_distinct(&self) -> bool {
+        self.data.iter().all_unique()
+    }
+
+    /// Maps every entry `x` to `scale * x + offset`, which maps a magic square to a magic square
+    /// with constant `scale * magic_sum + P::ORDER * offset`.
+    #[must_use]
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn affine(&self, scale: i64, offset: i64) -> Self {
+        Self {
+            data: self.data.iter().map(|&x| scale * x + offset).collect(),
+            magic_sum: scale * self.magic_sum + P::ORDER as i64 * offset,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl NonNormalSquare<O3> {
+    /// Creates the order 3 magic square in Lucas' parametrisation, with constant `3 * c`.
+    ///
+    /// Every order 3 magic square of distinct entries has this form, and the entries are
+    /// distinct when `0 < a < b` and `b != 2 * a`.
+    #[must_use]
+    pub fn lucas(a: i64, b: i64, c: i64) -> Self {
+        Self {
+            data: vec![
+                c - b,
+                c + (a + b),
+                c - a,
+                c - (a - b),
+                c,
+                c + (a - b),
+                c + a,
+                c - (a + b),
+                c + b,
+            ],
+            magic_sum: 3 * c,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: Params> From<&Construction<P>> for NonNormalSquare<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn from(item: &Construction<P>) -> Self {
+        Self {
+            data: item.square.data.iter().map(|&x| i64::from(x)).collect(),
+            magic_sum: i64::from(P::MAGIC_SUM),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<P: Params> fmt::Display for NonNormalSquare<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut a = self.data.iter();
+        for i in 1..=P::ORDER {
+            write!(f, "{:?}", a.by_ref().take(P::ORDER).collect_vec())?;
+            if i != P::ORDER {
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the magic constant admitted by a set of entries: their total divided by the order.
+#[allow(clippy::cast_possible_wrap)]
+pub(crate) fn magic_sum_of<P: Params>(entries: &[i64]) -> Result<i64, ParameterSetError> {
+    let total = entries.iter().sum::<i64>();
+    if total % P::ORDER as i64 != 0 {
+        return Err(ParameterSetError::InvalidElements(
+            "Entry total must be divisible by P::ORDER!".to_string(),
+        ));
+    }
+
+    Ok(total / P::ORDER as i64)
+}
+
+#[cfg(test)]
+mod test_non_normal {
+    use crate::{O3, O4};
+
+    use super::*;
+
+    #[test]
+    fn test_from_entries() {
+        let a = NonNormalSquare::<O3>::from_entries(vec![2, 7, 6, 9, 5, 1, 4, 3, 8]).unwrap();
+        assert_eq!(a.magic_sum, 15);
+        assert!(a.is_normal());
+
+        assert!(NonNormalSquare::<O3>::from_entries(vec![1, 2, 3]).is_err());
+        assert!(
+            NonNormalSquare::<O3>::from_entries(vec![1; 8].into_iter().chain([2]).collect())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_affine() {
+        let a = NonNormalSquare::from(&Construction::<O3>::siamese(1)).affine(1, 9);
+        assert_eq!(a.magic_sum, 42);
+        assert_eq!(a.entries(), (10..=18).collect::<Vec<_>>());
+
+        let b = NonNormalSquare::<O4>::new(vec![0; 16], 0)
+            .unwrap()
+            .affine(3, -2);
+        assert_eq!(b.magic_sum, -8);
+        assert!(!b.is_distinct());
+    }
+
+    #[test]
+    fn test_lucas() {
+        let a = NonNormalSquare::<O3>::lucas(1, 3, 5);
+        assert_eq!(a.magic_sum, 15);
+        assert!(a.is_normal());
+
+        let b = NonNormalSquare::<O3>::lucas(2, 7, -4);
+        assert!(b.is_distinct());
+        assert!(b.data.iter().any(|&x| x < 0));
+    }
+}
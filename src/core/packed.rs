@@ -0,0 +1,164 @@
+use std::fmt;
+use std::simd::prelude::*;
+
+use crate::{ParameterSetError, Permutation, Square, O4};
+
+/// An order 4 permutation packed into the sixteen nibbles of a `u64`.
+///
+/// Each entry `1..=16` is stored as `0..=15`, with the first entry in the most significant
+/// nibble. Comparing the packed integers therefore orders permutations lexicographically, which
+/// is the order of their indexes, and hashing one is hashing a single `u64`. Composition unpacks
+/// the nibbles into the bytes of a 16-lane vector and applies one byte shuffle.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{PackedPerm16, Permutation, O4};
+///
+/// let a = PackedPerm16::identity();
+/// assert_eq!(Permutation::from(a), Permutation::<O4>::identity());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackedPerm16(u64);
+
+impl PackedPerm16 {
+    /// Returns the identity permutation.
+    #[must_use]
+    pub const fn identity() -> Self {
+        Self(0x0123_4567_89AB_CDEF)
+    }
+
+    /// Returns the packed permutation, or `None` if the nibbles of `packed` are not each of
+    /// `0..=15` exactly once.
+    #[must_use]
+    pub fn from_u64(packed: u64) -> Option<Self> {
+        let seen = (0..16).fold(0u16, |seen, i| seen | 1 << (packed >> (4 * i) & 0xF));
+        (seen == u16::MAX).then_some(Self(packed))
+    }
+
+    /// Wraps `packed`, whose nibbles are already known to be each of `0..=15` exactly once.
+    #[must_use]
+    pub(crate) const fn from_u64_unchecked(packed: u64) -> Self {
+        Self(packed)
+    }
+
+    /// Returns the packed integer.
+    #[must_use]
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the entry at `index`, in `0..=15`.
+    ///
+    /// # Panics
+    /// - If `index` is not less than 16.
+    #[must_use]
+    pub fn get(self, index: usize) -> u8 {
+        assert!(index < 16, "Index out of range!");
+        (self.0 >> (60 - 4 * index) & 0xF) as u8
+    }
+
+    /// Unpacks the entries, in `0..=15`, into the lanes of a vector.
+    #[inline]
+    #[must_use]
+    pub fn to_bytes(self) -> u8x16 {
+        let bytes = u8x8::from_array(self.0.to_be_bytes());
+        let pairs = simd_swizzle!(bytes, [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7]);
+        let shifts = u8x16::from_array([4, 0, 4, 0, 4, 0, 4, 0, 4, 0, 4, 0, 4, 0, 4, 0]);
+        (pairs >> shifts) & u8x16::splat(0xF)
+    }
+
+    /// Packs the entries, in `0..=15`, held in the lanes of a vector.
+    #[inline]
+    #[must_use]
+    pub(crate) fn from_bytes(bytes: u8x16) -> Self {
+        let high = simd_swizzle!(bytes, [0, 2, 4, 6, 8, 10, 12, 14]);
+        let low = simd_swizzle!(bytes, [1, 3, 5, 7, 9, 11, 13, 15]);
+        Self(u64::from_be_bytes(((high << 4) | low).to_array()))
+    }
+}
+
+impl fmt::Display for PackedPerm16 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Permutation::from(*self))
+    }
+}
+
+impl From<PackedPerm16> for Permutation<O4> {
+    fn from(p: PackedPerm16) -> Self {
+        let data = p.to_bytes().cast::<u32>() + Simd::splat(1);
+        Square::<O4>::from_array(data.to_array()).to_perm()
+    }
+}
+
+impl TryFrom<Permutation<O4>> for PackedPerm16 {
+    type Error = ParameterSetError;
+
+    fn try_from(p: Permutation<O4>) -> Result<Self, Self::Error> {
+        let packed = p.square.data.iter().try_fold(0u64, |acc, &x| match x {
+            1..=16 => Some(acc << 4 | u64::from(x - 1)),
+            _ => None,
+        });
+
+        packed
+            .and_then(Self::from_u64)
+            .ok_or(ParameterSetError::InvalidElements(
+                "Elements are not a permutation of 1..=16!".to_string(),
+            ))
+    }
+}
+
+#[cfg(test)]
+mod test_packed {
+    use std::collections::BTreeSet;
+
+    use crate::{Enumerable, IndexConst};
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() -> Result<(), ParameterSetError> {
+        for k in (0..O4::MAX_INDEX).step_by(999_999_937) {
+            let p = Permutation::<O4>::kth(k);
+            let packed = PackedPerm16::try_from(p)?;
+
+            assert_eq!(Permutation::from(packed), p);
+            assert_eq!(PackedPerm16::from_bytes(packed.to_bytes()), packed);
+            assert_eq!(u32::from(packed.get(15)) + 1, p.square.data[15]);
+        }
+
+        assert_eq!(
+            PackedPerm16::try_from(Permutation::<O4>::identity())?,
+            PackedPerm16::identity()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut data = [1; 16];
+        assert!(
+            PackedPerm16::try_from(Permutation::<O4>::try_from(data.as_slice()).unwrap()).is_err()
+        );
+        data[0] = 17;
+        assert!(
+            PackedPerm16::try_from(Permutation::<O4>::try_from(data.as_slice()).unwrap()).is_err()
+        );
+        assert_eq!(PackedPerm16::from_u64(0), None);
+        assert_eq!(
+            PackedPerm16::from_u64(0xFEDC_BA98_7654_3210).map(PackedPerm16::as_u64),
+            Some(0xFEDC_BA98_7654_3210)
+        );
+    }
+
+    #[test]
+    fn test_ord() {
+        let perms = [5, 20_922_789_887_999, 0, 400, 80_867_885_530].map(Permutation::<O4>::kth);
+        let packed = perms.map(|p| PackedPerm16::try_from(p).unwrap());
+
+        let a = perms.into_iter().collect::<BTreeSet<_>>();
+        let b = packed.into_iter().collect::<BTreeSet<_>>();
+        assert!(a.into_iter().eq(b.into_iter().map(Permutation::from)));
+    }
+}
@@ -0,0 +1,230 @@
+use std::ops::Range;
+
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{Enumerable, EnumerableMarker, Params, Permutation, O3, O4, O5};
+
+/// The fewest indexes a `ParPermutationRange` splits, since each half costs a `kth` call.
+const MIN_SPLIT: u32 = 1024;
+
+/// A range of indexes, iterated as the permutations they enumerate.
+///
+/// The first permutation is computed with one `kth` call and each following one with
+/// `next_perm`, which is much cheaper. Its parallel iterator splits the range at index boundaries
+/// and seeds each half with one `kth` call.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{Enumerable, IndexConst, Permutation, PermutationRange, O3};
+/// use rayon::prelude::*;
+///
+/// let range = PermutationRange::<O3>::new(10..20);
+/// assert!(range.zip(10..20).all(|(p, k)| p == Permutation::<O3>::kth(k)));
+///
+/// let count = PermutationRange::<O3>::new(0..O3::MAX_INDEX)
+///     .into_par_iter()
+///     .count();
+/// assert_eq!(count, 362_880);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PermutationRange<P: Params + EnumerableMarker>
+where
+    [(); P::ELEMENTS]:,
+{
+    current: Option<Permutation<P>>,
+    start: P::Index,
+    end: P::Index,
+}
+
+impl<P: Params + EnumerableMarker> PermutationRange<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Returns the permutations at the indexes of `range`, in order.
+    #[must_use]
+    pub fn new(range: Range<P::Index>) -> Self {
+        Self {
+            current: None,
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    /// Returns the indexes not yet iterated.
+    #[must_use]
+    pub fn indexes(&self) -> Range<P::Index> {
+        self.start..self.end
+    }
+}
+
+/// A parallel iterator over a `PermutationRange`.
+#[derive(Clone, Debug)]
+pub struct ParPermutationRange<P: Params + EnumerableMarker>
+where
+    [(); P::ELEMENTS]:,
+{
+    range: PermutationRange<P>,
+}
+
+macro_rules! impl_range_for_enumerable_params {
+    ($p:tt, $t:ty) => {
+        impl Iterator for PermutationRange<$p> {
+            type Item = Permutation<$p>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.start >= self.end {
+                    return None;
+                }
+
+                let p = self
+                    .current
+                    .take()
+                    .unwrap_or_else(|| Permutation::<$p>::kth(self.start));
+                self.start += 1;
+                if self.start < self.end {
+                    let mut next = p;
+                    next.next_perm();
+                    self.current = Some(next);
+                }
+
+                Some(p)
+            }
+
+            /// Skips `n` permutations with `next_perm` when they are few, and with a single
+            /// `kth` call otherwise, so `step_by` stays cheap for long steps.
+            fn nth(&mut self, n: usize) -> Option<Self::Item> {
+                match <$t>::try_from(n) {
+                    Ok(n) if n < self.end.saturating_sub(self.start) => {
+                        if n as usize <= $p::ELEMENTS {
+                            for _ in 0..n {
+                                self.next();
+                            }
+                        } else {
+                            self.start += n;
+                            self.current = None;
+                        }
+                        self.next()
+                    }
+                    _ => {
+                        self.start = self.end.max(self.start);
+                        self.current = None;
+                        None
+                    }
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let len = self.end.saturating_sub(self.start);
+                usize::try_from(len).map_or((usize::MAX, None), |len| (len, Some(len)))
+            }
+        }
+
+        impl UnindexedProducer for PermutationRange<$p> {
+            type Item = Permutation<$p>;
+
+            fn split(self) -> (Self, Option<Self>) {
+                let len = self.end.saturating_sub(self.start);
+                if len < <$t>::from(MIN_SPLIT) {
+                    return (self, None);
+                }
+
+                let mid = self.start + len / 2;
+                let right = Self::new(mid..self.end);
+                (Self { end: mid, ..self }, Some(right))
+            }
+
+            fn fold_with<F: Folder<Self::Item>>(self, folder: F) -> F {
+                folder.consume_iter(self)
+            }
+        }
+
+        impl ParallelIterator for ParPermutationRange<$p> {
+            type Item = Permutation<$p>;
+
+            fn drive_unindexed<C: UnindexedConsumer<Self::Item>>(self, consumer: C) -> C::Result {
+                bridge_unindexed(self.range, consumer)
+            }
+        }
+
+        impl IntoParallelIterator for PermutationRange<$p> {
+            type Iter = ParPermutationRange<$p>;
+            type Item = Permutation<$p>;
+
+            fn into_par_iter(self) -> Self::Iter {
+                ParPermutationRange { range: self }
+            }
+        }
+    };
+}
+
+impl_range_for_enumerable_params!(O3, u32);
+impl_range_for_enumerable_params!(O4, u64);
+impl_range_for_enumerable_params!(O5, u128);
+
+#[cfg(test)]
+mod test_range {
+    use std::collections::BTreeSet;
+
+    use crate::{CheckVector, IndexConst};
+
+    use super::*;
+
+    #[test]
+    fn test_iter() {
+        assert!(PermutationRange::<O3>::new(0..O3::MAX_INDEX)
+            .eq((0..O3::MAX_INDEX).map(Permutation::<O3>::kth)));
+
+        let start = O4::MAX_INDEX - 5000;
+        assert!(PermutationRange::<O4>::new(start..O4::MAX_INDEX)
+            .eq((start..O4::MAX_INDEX).map(Permutation::<O4>::kth)));
+
+        let start = 12_345_678_901_234_567_890;
+        assert!(PermutationRange::<O5>::new(start..start + 5000)
+            .eq((start..start + 5000).map(Permutation::<O5>::kth)));
+
+        assert_eq!(PermutationRange::<O3>::new(5..5).next(), None);
+        let (start, end) = (9, 5);
+        assert_eq!(PermutationRange::<O3>::new(start..end).next(), None);
+    }
+
+    #[test]
+    fn test_nth() {
+        for step in [1, 3, 16, 17, 1000] {
+            assert!(PermutationRange::<O4>::new(777..50_000)
+                .step_by(step)
+                .eq((777..50_000).step_by(step).map(Permutation::<O4>::kth)));
+        }
+
+        let mut range = PermutationRange::<O3>::new(0..100);
+        assert_eq!(range.nth(100), None);
+        assert_eq!(range.indexes(), 100..100);
+        assert_eq!(range.size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    fn test_par_iter() {
+        let a = PermutationRange::<O3>::new(0..O3::MAX_INDEX)
+            .into_par_iter()
+            .filter_map(|p| p.check_v())
+            .collect::<BTreeSet<_>>();
+        let b = (0..O3::MAX_INDEX)
+            .filter_map(|k| Permutation::<O3>::kth(k).check_v())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 8);
+
+        let first = PermutationRange::<O3>::new(0..O3::MAX_INDEX)
+            .into_par_iter()
+            .find_map_first(|p| p.check_v());
+        assert_eq!(first, a.first().copied());
+
+        let start = 80_867_885_000;
+        let indexes = PermutationRange::<O4>::new(start..start + 100_000)
+            .into_par_iter()
+            .map(|p| p.index())
+            .collect::<Vec<_>>();
+        assert_eq!(indexes, (start..start + 100_000).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::{Construction, ParameterSetError, Params, RectParams};
+
+/// A rectangle of `R::ROWS` rows and `R::COLS` columns, stored row-major.
+///
+/// A magic rectangle holds `1..=R::ROWS * R::COLS` with every row summing to `R::ROW_SUM` and
+/// every column to `R::COL_SUM`. A square parameter set gives the rectangle with equal sides,
+/// which is magic when the square is semi-magic.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{MagicRectangle, R3x5};
+///
+/// let a = MagicRectangle::<R3x5>::construct();
+///
+/// assert!(a.check_n_s().is_some());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MagicRectangle<R: RectParams> {
+    pub data: Vec<u32>,
+    phantom: PhantomData<R>,
+}
+
+impl<R: RectParams> MagicRectangle<R> {
+    /// Creates a rectangle from its entries, row by row.
+    ///
+    /// # Errors
+    /// - If the number of entries does not match `R::ROWS * R::COLS`.
+    pub fn new(data: Vec<u32>) -> Result<Self, ParameterSetError> {
+        if data.len() != R::ROWS * R::COLS {
+            return Err(ParameterSetError::ElementCount(
+                "Item length does not match R::ROWS * R::COLS!".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            data,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns row `i`.
+    ///
+    /// # Panics
+    /// - If `i` is not less than `R::ROWS`.
+    #[must_use]
+    pub fn row(&self, i: usize) -> &[u32] {
+        &self.data[i * R::COLS..(i + 1) * R::COLS]
+    }
+
+    /// Returns column `j`.
+    ///
+    /// # Panics
+    /// - If `j` is not less than `R::COLS`.
+    #[must_use]
+    pub fn column(&self, j: usize) -> Vec<u32> {
+        assert!(j < R::COLS, "Column out of range!");
+        self.data.iter().skip(j).step_by(R::COLS).copied().collect()
+    }
+}
+
+impl<R: RectParams> TryFrom<&[u32]> for MagicRectangle<R> {
+    type Error = ParameterSetError;
+
+    fn try_from(item: &[u32]) -> Result<Self, Self::Error> {
+        Self::new(item.to_vec())
+    }
+}
+
+impl<P: Params> From<&Construction<P>> for MagicRectangle<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn from(item: &Construction<P>) -> Self {
+        Self {
+            data: item.square.data.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<R: RectParams> fmt::Display for MagicRectangle<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.data.chunks(R::COLS).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{row:?}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_rectangle {
+    use crate::{R2x4, R3x5, O3};
+
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert!(MagicRectangle::<R3x5>::new(vec![0; 15]).is_ok());
+        assert!(MagicRectangle::<R3x5>::new(vec![0; 9]).is_err());
+        assert_eq!((R3x5::ROW_SUM, R3x5::COL_SUM), (40, 24));
+        assert_eq!((O3::ROWS, O3::COLS, O3::ROW_SUM), (3, 3, 15));
+    }
+
+    #[test]
+    fn test_lines() {
+        let a = MagicRectangle::<R2x4>::try_from([1, 2, 3, 4, 5, 6, 7, 8].as_slice()).unwrap();
+
+        assert_eq!(a.row(1), &[5, 6, 7, 8]);
+        assert_eq!(a.column(2), vec![3, 7]);
+        assert_eq!(a.to_string(), "[1, 2, 3, 4]\n[5, 6, 7, 8]");
+    }
+
+    #[test]
+    fn test_square() {
+        let a = MagicRectangle::from(&Construction::<O3>::siamese(1));
+
+        assert_eq!(a.row(0).iter().sum::<u32>(), O3::ROW_SUM);
+        assert_eq!(a.column(0).iter().sum::<u32>(), O3::COL_SUM);
+    }
+}
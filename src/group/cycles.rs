@@ -1,6 +1,5 @@
-use crate::{Params, Permutation, Square};
-use std::mem::swap;
-use std::{collections::HashMap, fmt, marker::PhantomData};
+use crate::{gcd, Params, Permutation, Square};
+use std::{fmt, marker::PhantomData};
 
 /// A generic struct representing formal permutation presentation based on parameters `P`
 /// implementing the `Params` trait.
@@ -35,27 +34,10 @@ pub struct Cycles<P: Params> {
 }
 
 impl<P: Params + Copy> Cycles<P> {
-    /// Calculates the greatest common divisor of two integers.
-    #[inline]
-    fn gcd(mut a: usize, mut b: usize) -> usize {
-        if a == b {
-            return a;
-        }
-        if b > a {
-            swap(&mut a, &mut b);
-        }
-        while b > 0 {
-            let tmp = a;
-            a = b;
-            b = tmp % b;
-        }
-        a
-    }
-
     /// Calculates the least common multiple of two integers.
     #[inline]
     fn lcm(a: usize, b: usize) -> usize {
-        a * (b / Self::gcd(a, b))
+        a * (b / gcd(a, b))
     }
 
     /// Computes the order of the cycles.
@@ -85,21 +67,20 @@ impl<P: Params + Copy> Cycles<P> {
         self.k.push(value);
     }
 
-    /// Converts the `Cycles` instance into a `Permutation`.
+    /// Converts the `Cycles` instance into a `Permutation`. Each element of a cycle is sent to
+    /// the next, and where cycles overlap the last one wins.
+    ///
+    /// # Panics
+    /// - If an element of a cycle is not in `1..=P::ELEMENTS`.
     #[must_use]
     pub fn into_permutation(&self) -> Permutation<P>
     where
         [(); P::ELEMENTS]:,
     {
-        let mut s = [0; P::ELEMENTS];
-        // self.k.reverse();
-        for (index, item) in s.iter_mut().enumerate() {
-            *item = (index + 1) as u32;
-            for j in &self.k {
-                if let Some(p) = j.iter().position(|&a| a == (index + 1)) {
-                    let next = (p + 1) % j.len();
-                    *item = j[next] as u32;
-                }
+        let mut s = Permutation::<P>::identity().square.data;
+        for cycle in &self.k {
+            for (&a, &b) in cycle.iter().zip(cycle.iter().cycle().skip(1)) {
+                s[a - 1] = b as u32;
             }
         }
 
@@ -140,56 +121,91 @@ impl<P: Params + Copy> Permutation<P>
 where
     [(); P::ELEMENTS]:,
 {
-    /// Converts the Permutation into cyclic notation represented by Cycles.
+    /// Calls `f` with every cycle of the permutation of length greater than one, in order of
+    /// their smallest elements, each starting at its smallest element. The cycles are written
+    /// into a buffer on the stack, so nothing is allocated.
+    ///
     /// # Panics
-    /// - If key to corresponding map does not exist.
-    #[must_use]
-    pub fn cyclic_notation(&self) -> Cycles<P> {
-        let hb: HashMap<usize, usize> = (1..=P::ELEMENTS)
-            .zip((self.square.data).into_iter().map(|a| a as usize))
-            .collect();
-        let b = (1..=P::ELEMENTS).zip((self.square.data).into_iter().map(|a| a as usize));
+    /// - If an entry is not in `1..=P::ELEMENTS`.
+    pub fn for_each_cycle<F: FnMut(&[usize])>(&self, mut f: F) {
+        let mut visited = [false; P::ELEMENTS];
+        let mut cycle = [0usize; P::ELEMENTS];
 
-        let mut all = vec![];
-        let mut cycle = vec![];
-        let mut taken = vec![];
+        for start in 0..P::ELEMENTS {
+            let mut len = 0;
+            let mut k = start;
+            while !visited[k] {
+                visited[k] = true;
+                cycle[len] = k + 1;
+                len += 1;
+                k = self.square.data[k] as usize - 1;
+            }
 
-        for pair in b {
-            if !taken.contains(&pair) {
-                let mut k = pair.0;
-                for _ in 0..P::ELEMENTS {
-                    let v = *hb.get(&k).unwrap();
-                    if cycle.contains(&v) {
-                        break;
-                    }
-                    cycle.push(v);
-                    taken.push((k, v));
-                    k = v;
-                }
-                let s = cycle
-                    .iter()
-                    .position(|&a| &a == cycle.clone().iter().min().unwrap())
-                    .unwrap();
-                cycle.rotate_left(s);
-                if cycle.len() > 1 {
-                    all.push(cycle.clone());
-                }
-                cycle.clear();
+            if len > 1 {
+                f(&cycle[..len]);
             }
         }
+    }
+
+    /// Converts the Permutation into cyclic notation represented by Cycles.
+    ///
+    /// # Panics
+    /// - If an entry is not in `1..=P::ELEMENTS`.
+    #[must_use]
+    pub fn cyclic_notation(&self) -> Cycles<P> {
+        let mut all = vec![];
+        self.for_each_cycle(|cycle| all.push(cycle.to_vec()));
 
         Cycles {
             k: all,
             phantom: PhantomData,
         }
     }
+
+    /// Computes the order of the permutation, the least common multiple of its cycle lengths,
+    /// without building its cycles.
+    ///
+    /// # Panics
+    /// - If an entry is not in `1..=P::ELEMENTS`.
+    #[must_use]
+    pub fn order(&self) -> usize {
+        let mut order = 1;
+        self.for_each_cycle(|cycle| order = Cycles::<P>::lcm(order, cycle.len()));
+        order
+    }
 }
 
 #[cfg(test)]
 mod test_ops {
-    use crate::{CheckVector, Cycles, Enumerable, Permutation, Square, O3, O4};
+    use crate::{CheckVector, Cycles, Enumerable, Params, Permutation, Square, O16, O3, O4};
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
     use rayon::prelude::*;
 
+    /// Cyclic notation by following each entry until it repeats, as before `for_each_cycle`.
+    fn reference_cycles<P: Params + Copy>(a: &Permutation<P>) -> Vec<Vec<usize>>
+    where
+        [(); P::ELEMENTS]:,
+    {
+        let mut all = vec![];
+        let mut taken = vec![];
+        for start in 1..=P::ELEMENTS {
+            if taken.contains(&start) {
+                continue;
+            }
+            let mut cycle = vec![start];
+            let mut k = a.square.data[start - 1] as usize;
+            while k != start {
+                cycle.push(k);
+                k = a.square.data[k - 1] as usize;
+            }
+            taken.extend(&cycle);
+            if cycle.len() > 1 {
+                all.push(cycle);
+            }
+        }
+        all
+    }
+
     #[test]
     #[ignore = "debugging"]
     fn test_display() {
@@ -263,4 +279,32 @@ mod test_ops {
         let a = Permutation::<O3>::kth(310011);
         assert_eq!(a.cyclic_notation().order(), 15)
     }
+
+    #[test]
+    fn test_matches_reference() {
+        for k in (0..362_880).step_by(997) {
+            let a = Permutation::<O3>::kth(k);
+            let b = a.cyclic_notation();
+
+            assert_eq!(b.k, reference_cycles(&a));
+            assert_eq!(b.into_permutation(), a);
+            assert_eq!(a.order(), b.order());
+            assert_eq!(
+                a.pow(i32::try_from(a.order()).unwrap()),
+                Permutation::identity()
+            );
+        }
+
+        let mut rng = StdRng::seed_from_u64(16);
+        let mut data = Permutation::<O16>::identity().square.data;
+        for _ in 0..20 {
+            data.shuffle(&mut rng);
+            let a = Square::<O16>::from_array(data).to_perm();
+            let b = a.cyclic_notation();
+
+            assert_eq!(b.k, reference_cycles(&a));
+            assert_eq!(b.into_permutation(), a);
+            assert_eq!(a.order(), b.order());
+        }
+    }
 }
@@ -1,31 +1,133 @@
-use crate::{Enumerable, Params, Permutation, O3, O4, O5};
+use crate::{
+    CompactPermutation, Enumerable, IndexConst, PackedPerm16, Params, Permutation, Square, O3, O4,
+    O5,
+};
 use std::collections::HashSet;
 
+/// Builds the cells read by each element of the dihedral group of a square of order `n` with `N`
+/// cells, in the order of `Permutation::D4_TABLES`.
+const fn d4_tables<const N: usize>(n: usize) -> [[usize; N]; 8] {
+    let mut tables = [[0; N]; 8];
+    let mut i = 0;
+    while i < N {
+        tables[0][i] = i;
+        tables[4][i] = (i / n) * n + (n - 1 - i % n);
+        i += 1;
+    }
+
+    // Composing `g` with a rotation reads the cells of `g` at the cells read by the rotation.
+    let mut g = 1;
+    while g < 8 {
+        if g != 4 {
+            let mut i = 0;
+            while i < N {
+                tables[g][i] = tables[g - 1][(i % n) * n + (n - 1 - i / n)];
+                i += 1;
+            }
+        }
+        g += 1;
+    }
+
+    tables
+}
+
 impl<P: Params + Copy> Permutation<P>
 where
     [(); P::ELEMENTS]:,
 {
+    /// The cells read by each element `g` of the dihedral group: cell `i` of `a * g` is cell
+    /// `D4_TABLES[g][i]` of `a`. The elements are `e`, `r`, `r^2`, `r^3`, `s`, `s r`, `s r^2` and
+    /// `s r^3`, where `r` is `identity().rotate_90()` and `s` is `identity().reflect_x()`. The
+    /// tables are built at compile time.
+    pub const D4_TABLES: [[usize; P::ELEMENTS]; 8] = d4_tables::<{ P::ELEMENTS }>(P::ORDER);
+
+    /// Generate the dihedral group for a Permutation, as the images `a * g` for the elements
+    /// `g` in the order of `D4_TABLES`. The images of a square with symmetries repeat.
+    #[must_use]
+    pub fn generate_d_array(&self) -> [Permutation<P>; 8] {
+        let tables = &Self::D4_TABLES;
+        std::array::from_fn(|g| {
+            Square::from_array(std::array::from_fn(|i| self.square.data[tables[g][i]])).to_perm()
+        })
+    }
+
     /// Generate the dihedral group for a Square or Permutation
     ///
     /// # Note:
     /// Because this function uses a `HashSet`, the result will contain non-trivial ordering
     #[must_use]
     pub fn generate_d(&self) -> HashSet<Permutation<P>> {
-        let mut set = HashSet::new();
-        let a = *self;
-        let r = Self::identity().square.rotate_90().to_perm();
-        let s = Self::identity().square.reflect_x().to_perm();
-
-        set.insert(a);
-        set.insert(a * r);
-        set.insert(a * r.pow(2));
-        set.insert(a * r.pow(3));
-
-        set.insert(a * s);
-        set.insert(a * s * r);
-        set.insert(a * s * r.pow(2));
-        set.insert(a * r * s);
-        set
+        self.generate_d_array().into_iter().collect()
+    }
+
+    /// Returns whether the permutation is in canonical D4 position: its top-left corner is the
+    /// smallest corner and its top-right corner is less than its bottom-left corner. Exactly one
+    /// of the images in `generate_d_array` is canonical.
+    #[must_use]
+    pub fn is_d4_canonical(&self) -> bool {
+        self.d4_violation().is_none()
+    }
+
+    /// Returns the first cell at which the corners fail to be in canonical D4 position. Each
+    /// check only reads the cells up to it, so every permutation sharing those cells fails too.
+    fn d4_violation(&self) -> Option<usize> {
+        let (n, a) = (P::ORDER, &self.square.data);
+        let (top_right, bottom_left, bottom_right) = (n - 1, P::ELEMENTS - n, P::ELEMENTS - 1);
+
+        if a[0] > a[top_right] {
+            Some(top_right)
+        } else if a[0] > a[bottom_left] || a[top_right] > a[bottom_left] {
+            Some(bottom_left)
+        } else if a[0] > a[bottom_right] {
+            Some(bottom_right)
+        } else {
+            None
+        }
+    }
+}
+
+impl<P: Params + Copy> CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Generate the dihedral group for a compact Permutation
+    ///
+    /// # Note:
+    /// Because this function uses a `HashSet`, the result will contain non-trivial ordering
+    #[must_use]
+    pub fn generate_d(&self) -> HashSet<CompactPermutation<P>> {
+        Permutation::<P>::D4_TABLES
+            .iter()
+            .map(|table| Self {
+                data: std::array::from_fn(|i| self.data[table[i]]),
+            })
+            .collect()
+    }
+}
+
+/// `Permutation::<O4>::identity().rotate_90()`, packed.
+const PACKED_ROTATE_90: PackedPerm16 = PackedPerm16::from_u64_unchecked(0x37BF_26AE_159D_048C);
+/// `Permutation::<O4>::identity().reflect_x()`, packed.
+const PACKED_REFLECT_X: PackedPerm16 = PackedPerm16::from_u64_unchecked(0x3210_7654_BA98_FEDC);
+
+impl PackedPerm16 {
+    /// Generate the dihedral group for a packed Permutation, in the order of
+    /// `Permutation::D4_TABLES`. The images of a square with symmetries repeat.
+    #[must_use]
+    pub fn generate_d(self) -> [PackedPerm16; 8] {
+        let (a, r, s) = (self, PACKED_ROTATE_90, PACKED_REFLECT_X);
+        let r2 = r * r;
+
+        [
+            a,
+            a * r,
+            a * r2,
+            a * r2 * r,
+            a * s,
+            a * s * r,
+            a * s * r2,
+            a * r * s,
+        ]
     }
 }
 
@@ -38,21 +140,60 @@ macro_rules! impl_generate_d_indexes_for_enumerable_order {
             /// Because this function uses a `HashSet`, the result will contain non-trivial ordering
             #[must_use]
             pub fn generate_d_indexes(&self) -> HashSet<$t> {
-                let mut set = HashSet::new();
-                let a = *self;
-                let r = Self::identity().square.rotate_90().to_perm();
-                let s = Self::identity().square.reflect_x().to_perm();
-
-                set.insert(a.index());
-                set.insert((a * r).index());
-                set.insert((a * r.pow(2)).index());
-                set.insert((a * r.pow(3)).index());
-
-                set.insert((a * s).index());
-                set.insert((a * s * r).index());
-                set.insert((a * s * r.pow(2)).index());
-                set.insert((a * r * s).index());
-                set
+                self.generate_d_array()
+                    .iter()
+                    .map(Enumerable::index)
+                    .collect()
+            }
+
+            /// Returns the first permutation in canonical D4 position at an index of at least
+            /// `k`, with its index, or `None` if there is none.
+            ///
+            /// Indexes enumerate permutations lexicographically, so the permutations sharing
+            /// their first `i + 1` cells fill an aligned block of `(ELEMENTS - 1 - i)!` indexes,
+            /// set by the leading factorial digits. When the corners fail at cell `i` the whole
+            /// block is skipped at once.
+            #[must_use]
+            pub fn next_canonical(k: $t) -> Option<(Self, $t)> {
+                (k < <$p as IndexConst<$t>>::MAX_INDEX)
+                    .then(|| Self::kth(k))
+                    .and_then(|p| Self::canonical_from(p, k))
+            }
+
+            /// Iterates over the permutations in canonical D4 position at the indexes in
+            /// `start..end`, skipping the blocks of indexes that fail as in `next_canonical`.
+            /// Each orbit of the dihedral group has exactly one canonical member, which
+            /// `generate_d_array` expands back to the orbit.
+            ///
+            /// Only the first permutation is computed with `kth`; the rest are walked to with
+            /// `next_perm`, so split a range into contiguous parts to share it between threads.
+            pub fn canonical_range(start: $t, end: $t) -> impl Iterator<Item = Self> {
+                let mut next = Self::next_canonical(start);
+                std::iter::from_fn(move || {
+                    let (p, k) = next.take().filter(|&(_, k)| k < end)?;
+                    let mut q = p;
+                    next = q
+                        .next_perm()
+                        .is_some()
+                        .then_some(q)
+                        .and_then(|q| Self::canonical_from(q, k + 1));
+
+                    Some(p)
+                })
+            }
+
+            /// Skips from `p`, at index `k`, to the first permutation in canonical D4 position
+            /// after it, skipping each failing block by sorting its free cells into the block's
+            /// last permutation and stepping past it.
+            fn canonical_from(mut p: Self, mut k: $t) -> Option<(Self, $t)> {
+                while let Some(i) = p.d4_violation() {
+                    let block = (1..=(<$p as Params>::ELEMENTS - 1 - i) as $t).product::<$t>();
+                    p.square.data[i + 1..].sort_unstable_by(|a, b| b.cmp(a));
+                    p.next_perm()?;
+                    k = (k / block + 1) * block;
+                }
+
+                Some((p, k))
             }
         }
     };
@@ -64,7 +205,7 @@ impl_generate_d_indexes_for_enumerable_order!(O5, u128);
 
 #[cfg(test)]
 mod test_group {
-    use crate::{Permutation, O3, O4};
+    use crate::{IndexConst, Permutation, O16, O3, O4, O5};
 
     use super::*;
 
@@ -111,4 +252,105 @@ mod test_group {
         assert_eq!(8, b.len());
         assert!(!b.is_disjoint(&c));
     }
+
+    #[test]
+    fn test_generate_d_compact() {
+        let a = Permutation::<O4>::kth(80_867_885_530);
+        let b = CompactPermutation::try_from(a).unwrap().generate_d();
+
+        assert_eq!(
+            b.into_iter().map(Permutation::from).collect::<HashSet<_>>(),
+            a.generate_d()
+        );
+    }
+
+    #[test]
+    fn test_generate_d_packed() {
+        let a = Permutation::<O4>::kth(80_867_885_530);
+        let b = PackedPerm16::try_from(a).unwrap().generate_d();
+
+        assert_eq!(
+            Permutation::from(PACKED_ROTATE_90),
+            Permutation::<O4>::identity().rotate_90()
+        );
+        assert_eq!(
+            Permutation::from(PACKED_REFLECT_X),
+            Permutation::<O4>::identity().reflect_x()
+        );
+        assert_eq!(
+            b.into_iter().map(Permutation::from).collect::<HashSet<_>>(),
+            a.generate_d()
+        );
+    }
+
+    #[test]
+    fn test_generate_d_array() {
+        fn check<P: Params + Copy + std::fmt::Debug>(a: Permutation<P>)
+        where
+            [(); P::ELEMENTS]:,
+        {
+            let r = Permutation::<P>::identity().rotate_90();
+            let s = Permutation::<P>::identity().reflect_x();
+            let elements = [
+                Permutation::identity(),
+                r,
+                r * r,
+                r * r * r,
+                s,
+                s * r,
+                s * r * r,
+                s * r * r * r,
+            ];
+
+            for (image, g) in a.generate_d_array().into_iter().zip(elements) {
+                assert_eq!(image, a * g);
+            }
+        }
+
+        check(Permutation::<O3>::kth(69_074));
+        check(Permutation::<O4>::kth(80_867_885_530));
+        check(Permutation::<O5>::kth(12_345_678_901_234));
+        check(
+            Square::<O16>::from_array(std::array::from_fn(|i| (i as u32 * 7) % 256 + 1)).to_perm(),
+        );
+        assert_eq!(
+            Permutation::<O4>::D4_TABLES[1],
+            [3, 7, 11, 15, 2, 6, 10, 14, 1, 5, 9, 13, 0, 4, 8, 12]
+        );
+    }
+
+    #[test]
+    fn test_canonical_orbits() {
+        for k in (0..O4::MAX_INDEX).step_by(999_999_937) {
+            let images = Permutation::<O4>::kth(k).generate_d_array();
+            assert_eq!(images.iter().filter(|p| p.is_d4_canonical()).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_canonical_range_3() {
+        let canonical = (0..O3::MAX_INDEX)
+            .filter(|&k| Permutation::<O3>::kth(k).is_d4_canonical())
+            .collect::<Vec<_>>();
+        assert_eq!(canonical.len() as u32, O3::MAX_INDEX / 8);
+
+        let scanned = Permutation::<O3>::canonical_range(0, O3::MAX_INDEX)
+            .map(|p| p.index())
+            .collect::<Vec<_>>();
+        assert_eq!(scanned, canonical);
+
+        let expected = canonical
+            .iter()
+            .copied()
+            .filter(|k| (100..300_000).contains(k))
+            .collect::<Vec<_>>();
+        let bounds = [100, 101, 40_320, 200_003, 300_000];
+        let split = bounds
+            .windows(2)
+            .flat_map(|w| Permutation::<O3>::canonical_range(w[0], w[1]))
+            .map(|p| p.index())
+            .collect::<Vec<_>>();
+        assert_eq!(split, expected);
+        assert_eq!(Permutation::<O3>::next_canonical(O3::MAX_INDEX), None);
+    }
 }
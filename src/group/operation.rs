@@ -1,5 +1,4 @@
-use crate::{Params, Permutation, Square};
-use std::collections::HashMap;
+use crate::{Cell, CompactPermutation, PackedPerm16, Params, Permutation, Square};
 use std::ops::{Mul, MulAssign};
 
 #[allow(clippy::suspicious_arithmetic_impl)]
@@ -36,20 +35,20 @@ impl<P: Params + Copy> Permutation<P>
 where
     [(); P::ELEMENTS]:,
 {
-    /// Computes the inverse of the permutation.
+    /// Computes the inverse of the permutation, by writing each position at its entry.
     /// # Panics
-    /// - If key to corresponding map does not exist.
+    /// - If an entry is not in `1..=P::ELEMENTS`.
+    /// - If an entry repeats, so the square is not a permutation.
     #[must_use]
     pub fn inv(&self) -> Self {
-        let map = self
-            .square
-            .data
-            .into_iter()
-            .zip(1..=(P::ELEMENTS as u32))
-            .collect::<HashMap<_, _>>();
         let mut s = [0u32; P::ELEMENTS];
-        for (i, key) in s.iter_mut().zip(1..=(P::ELEMENTS as u32)) {
-            *i = *map.get(&key).unwrap();
+        for (position, &x) in (1..=P::ELEMENTS as u32).zip(self.square.data.iter()) {
+            let slot = &mut s[x as usize - 1];
+            assert!(
+                *slot == 0,
+                "Entry {x} repeats, the square is not a permutation!"
+            );
+            *slot = position;
         }
 
         Square::<P>::from_array(s).to_perm()
@@ -60,17 +59,20 @@ impl<P: Params + Copy> Permutation<P>
 where
     [(); P::ELEMENTS]:,
 {
-    /// Raises the permutation to the power of `n`.
+    /// Raises the permutation to the power of `n`, by squaring and multiplying.
     #[must_use]
     pub fn pow(&self, n: i32) -> Self {
-        if n == 0 {
-            return Permutation::identity();
+        let mut base = if n.is_negative() { self.inv() } else { *self };
+        let mut result = Permutation::identity();
+        let mut exponent = n.unsigned_abs();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
         }
-        let mut init = *self;
-        if n.is_negative() {
-            init = init.inv();
-        }
-        (1..n.abs()).map(|_| init).fold(init, |a, b| b * a)
+        result
     }
 }
 
@@ -85,9 +87,123 @@ where
     }
 }
 
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl<P: Params + Copy> Mul for CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            data: rhs.data.map(|x| self.data[x.to_u32() as usize - 1]),
+        }
+    }
+}
+
+#[allow(clippy::suspicious_op_assign_impl)]
+impl<P: Params + Copy> MulAssign for CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl<P: Params + Copy> CompactPermutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Computes the inverse of the permutation, by writing each position at its entry.
+    /// # Panics
+    /// - If an entry is not in `1..=P::ELEMENTS`.
+    /// - If an entry repeats, so the square is not a permutation.
+    #[must_use]
+    pub fn inv(&self) -> Self {
+        let identity = Self::identity();
+        let mut data = [P::Cell::default(); P::ELEMENTS];
+        for (&x, &position) in self.data.iter().zip(identity.data.iter()) {
+            let slot = &mut data[x.to_u32() as usize - 1];
+            assert!(
+                *slot == P::Cell::default(),
+                "Entry {x} repeats, the square is not a permutation!"
+            );
+            *slot = position;
+        }
+
+        Self { data }
+    }
+
+    /// Raises the permutation to the power of `n`, by squaring and multiplying.
+    #[must_use]
+    pub fn pow(&self, n: i32) -> Self {
+        let mut base = if n.is_negative() { self.inv() } else { *self };
+        let mut result = Self::identity();
+        let mut exponent = n.unsigned_abs();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// Conjugates the permutation by another permutation.
+    #[must_use]
+    pub fn conj(&self, conjugate: Self) -> Self {
+        conjugate.inv() * *self * conjugate
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Mul for PackedPerm16 {
+    type Output = Self;
+
+    /// Composes two packed permutations with a single byte shuffle, `(a * b)[i] = a[b[i]]`.
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_bytes(self.to_bytes().swizzle_dyn(rhs.to_bytes()))
+    }
+}
+
+#[allow(clippy::suspicious_op_assign_impl)]
+impl MulAssign for PackedPerm16 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl PackedPerm16 {
+    /// Computes the inverse of the permutation. Each position is written into the nibble of its
+    /// entry, a scatter that no byte shuffle can express, so the nibbles are written one by one.
+    #[inline]
+    #[must_use]
+    pub fn inv(self) -> Self {
+        let packed = self.as_u64();
+        let inverse = (0..16u64).fold(0, |acc, i| {
+            let entry = packed >> (60 - 4 * i) & 0xF;
+            acc | i << (60 - 4 * entry)
+        });
+
+        PackedPerm16::from_u64_unchecked(inverse)
+    }
+
+    /// Conjugates the permutation by another permutation.
+    #[must_use]
+    pub fn conj(self, conjugate: Self) -> Self {
+        conjugate.inv() * self * conjugate
+    }
+}
+
 #[cfg(test)]
 mod test_ops {
-    use crate::{Enumerable, Permutation, O3};
+    use crate::{
+        CompactPermutation, Enumerable, IndexConst, PackedPerm16, Permutation, Square, O20, O3, O4,
+    };
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 
     #[test]
     fn test_mul() {
@@ -129,6 +245,13 @@ mod test_ops {
         assert_eq!(b.inv() * a.inv(), (a * b).inv());
     }
 
+    #[test]
+    #[should_panic(expected = "not a permutation")]
+    fn test_inv_repeated() {
+        let a = Permutation::<O3>::try_from([1, 1, 3, 4, 5, 6, 7, 8, 9].as_slice()).unwrap();
+        let _ = a.inv();
+    }
+
     #[test]
     fn test_conj() {
         let a = Permutation::<O3>::identity().rotate_90();
@@ -136,4 +259,65 @@ mod test_ops {
         println!("{}", b.conj(a));
         println!("{}", a.conj(b));
     }
+
+    #[test]
+    fn test_compact() {
+        let r = CompactPermutation::try_from(Permutation::<O4>::identity().rotate_90()).unwrap();
+        for k in (0..O4::MAX_INDEX).step_by(3_000_000_019) {
+            let a = Permutation::<O4>::kth(k);
+            let b = Permutation::<O4>::kth(O4::MAX_INDEX - 1 - k);
+            let (ca, cb) = (
+                CompactPermutation::<O4>::kth(k),
+                CompactPermutation::try_from(b).unwrap(),
+            );
+
+            assert_eq!(Permutation::from(ca * cb), a * b);
+            assert_eq!(Permutation::from(ca.inv()), a.inv());
+            assert_eq!(Permutation::from(ca.pow(-3)), a.pow(-3));
+            assert_eq!(Permutation::from(ca.conj(r)), a.conj(r.into()));
+            assert_eq!(ca * ca.inv(), CompactPermutation::identity());
+        }
+    }
+
+    #[test]
+    fn test_packed() {
+        let r = Permutation::<O4>::identity().rotate_90();
+        let pr = PackedPerm16::try_from(r).unwrap();
+        for k in (0..O4::MAX_INDEX).step_by(3_000_000_019) {
+            let a = Permutation::<O4>::kth(k);
+            let b = Permutation::<O4>::kth(O4::MAX_INDEX - 1 - k);
+            let (pa, pb) = (
+                PackedPerm16::try_from(a).unwrap(),
+                PackedPerm16::try_from(b).unwrap(),
+            );
+
+            assert_eq!(Permutation::from(pa * pb), a * b);
+            assert_eq!(Permutation::from(pa.inv()), a.inv());
+            assert_eq!(Permutation::from(pa.conj(pr)), a.conj(r));
+            assert_eq!(pa * pa.inv(), PackedPerm16::identity());
+        }
+    }
+
+    #[test]
+    fn test_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(20);
+        let mut data = Permutation::<O20>::identity().square.data;
+        for _ in 0..20 {
+            data.shuffle(&mut rng);
+            let a = Square::<O20>::from_array(data).to_perm();
+
+            // The inverse sends each entry back to its position, found by search.
+            let positions =
+                (1..=400).map(|x| data.iter().position(|&y| y == x).unwrap() as u32 + 1);
+            assert!(a.inv().square.data.into_iter().eq(positions));
+
+            // Powers by repeated multiplication.
+            let mut power = Permutation::identity();
+            for n in 0..40 {
+                assert_eq!(a.pow(n), power);
+                assert_eq!(a.pow(-n), power.inv());
+                power *= a;
+            }
+        }
+    }
 }
@@ -0,0 +1,162 @@
+use crate::latin::square::is_orthogonal_pair;
+use crate::{Construction, LatinSquare, ParameterSetError, Params, Permutation, VecSquare};
+
+/// The pair of component squares `(A, B)` of a square `M` with entries `1..=n²`, defined by
+/// `M = n·A + B + 1` with every entry of `A` and `B` in `0..n`.
+///
+/// The components always superimpose to distinct pairs, but they need not be Latin. A square
+/// whose components are both Latin is said to be Euler-type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EulerComponents<P: Params> {
+    pub a: VecSquare<P>,
+    pub b: VecSquare<P>,
+}
+
+impl<P: Params> EulerComponents<P> {
+    /// Splits a slice of entries in `1..=n²` into its component squares.
+    fn from_entries(entries: &[u32]) -> Option<Self> {
+        let n = P::ORDER as u32;
+        if entries.len() != P::ELEMENTS || entries.iter().any(|&m| m == 0 || m > n * n) {
+            return None;
+        }
+
+        let (a, b) = entries.iter().map(|&m| ((m - 1) / n, (m - 1) % n)).unzip();
+        Some(Self {
+            a: VecSquare::from_vec(a),
+            b: VecSquare::from_vec(b),
+        })
+    }
+
+    /// Checks whether both components are Latin squares.
+    #[must_use]
+    pub fn is_euler_type(&self) -> bool {
+        self.a.is_latin() && self.b.is_latin()
+    }
+
+    /// Converts the components into a pair of orthogonal Latin squares, if both are Latin.
+    #[must_use]
+    pub fn into_latin(self) -> Option<(LatinSquare<P>, LatinSquare<P>)> {
+        if self.is_euler_type() {
+            Some((
+                LatinSquare { square: self.a },
+                LatinSquare { square: self.b },
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+impl<P: Params> Construction<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Creates a new `Construction` from a pair of orthogonal Latin squares using Euler's method,
+    /// `M = n·A + B + 1`.
+    ///
+    /// The result contains each of `1..=n²` exactly once. It is magic when both `A` and `B` are
+    /// diagonal Latin squares.
+    ///
+    /// # Errors
+    /// - If the Latin squares are not orthogonal.
+    pub fn euler(a: &LatinSquare<P>, b: &LatinSquare<P>) -> Result<Self, ParameterSetError> {
+        if !is_orthogonal_pair::<P>(&a.square.data, &b.square.data) {
+            return Err(ParameterSetError::InvalidElements(
+                "Latin squares are not orthogonal!".to_string(),
+            ));
+        }
+
+        let data = a
+            .square
+            .data
+            .iter()
+            .zip(&b.square.data)
+            .map(|(&x, &y)| P::ORDER as u32 * x + y + 1)
+            .collect();
+
+        Ok(Self {
+            square: VecSquare::from_vec(data),
+        })
+    }
+
+    /// Decomposes the construction into its Euler component squares.
+    ///
+    /// Returns `None` if any entry lies outside `1..=n²`.
+    #[must_use]
+    pub fn euler_components(&self) -> Option<EulerComponents<P>> {
+        EulerComponents::from_entries(&self.square.data)
+    }
+}
+
+impl<P: Params> Permutation<P>
+where
+    [(); P::ELEMENTS]:,
+{
+    /// Decomposes the permutation into its Euler component squares.
+    ///
+    /// # Panics
+    /// - If the permutation contains an entry outside `1..=n²`.
+    #[must_use]
+    pub fn euler_components(&self) -> EulerComponents<P> {
+        EulerComponents::from_entries(&self.square.data).expect("Entries must lie in 1..=n²!")
+    }
+}
+
+#[cfg(test)]
+mod test_euler {
+    use crate::{CheckVector, Enumerable, O25, O3, O4, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_euler_4() -> Result<(), ParameterSetError> {
+        let (a, b) = LatinSquare::<O4>::diagonal_pair().unwrap();
+        let m = Construction::euler(&a, &b)?;
+
+        assert!(m.check_v().is_some());
+        assert_eq!(m.euler_components().unwrap().into_latin(), Some((a, b)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_euler_n() -> Result<(), ParameterSetError> {
+        let (a, b) = LatinSquare::<O5>::diagonal_pair().unwrap();
+        assert!(Construction::euler(&a, &b)?.check_n_s().is_some());
+
+        let (a, b) = LatinSquare::<O25>::diagonal_pair().unwrap();
+        assert!(Construction::euler(&a, &b)?.check_n_s().is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_euler_not_orthogonal() {
+        let a = LatinSquare::<O5>::cyclic(1);
+        assert!(Construction::euler(&a, &a).is_err());
+    }
+
+    #[test]
+    fn test_components() {
+        // The Lo Shu square is Euler-type even though its components are not diagonal.
+        let (a, b) = Permutation::<O3>::kth(69074)
+            .euler_components()
+            .into_latin()
+            .unwrap();
+        assert!(a.is_orthogonal(&b));
+        assert!(!a.is_diagonal() && !b.is_diagonal());
+
+        let a = Permutation::<O4>::kth(80867885530).euler_components();
+        assert_eq!(
+            a.a,
+            VecSquare::from_vec(vec![0, 0, 3, 3, 2, 3, 0, 1, 3, 1, 2, 0, 1, 2, 1, 2])
+        );
+        assert_eq!(
+            a.b,
+            VecSquare::from_vec(vec![0, 1, 2, 3, 3, 1, 2, 0, 0, 2, 1, 3, 3, 2, 1, 0])
+        );
+        assert!(!a.is_euler_type());
+
+        assert!(Construction::<O3>::zeros().euler_components().is_none());
+    }
+}
@@ -0,0 +1,157 @@
+/// Arithmetic tables for the finite field GF(p^k).
+///
+/// Elements are labelled `0..p^k` by reading the base-`p` digits of the label as the coefficients
+/// of a polynomial over GF(p), least significant digit first. Multiplication is performed modulo
+/// the first monic irreducible polynomial of degree `k` found by trial division.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GaloisField {
+    pub(crate) order: usize,
+    add: Vec<usize>,
+    mul: Vec<usize>,
+}
+
+impl GaloisField {
+    /// Builds the field of order `q`, or returns `None` if `q` is not a prime power.
+    pub(crate) fn new(q: usize) -> Option<Self> {
+        let (p, k) = prime_power(q)?;
+        let modulus = irreducible(p, k);
+
+        let mut add = vec![0; q * q];
+        let mut mul = vec![0; q * q];
+        for x in 0..q {
+            for y in 0..q {
+                add[x * q + y] = from_digits(
+                    &to_digits(x, p, k)
+                        .iter()
+                        .zip(to_digits(y, p, k))
+                        .map(|(a, b)| (a + b) % p)
+                        .collect::<Vec<_>>(),
+                    p,
+                );
+                mul[x * q + y] = from_digits(
+                    &poly_mod(
+                        &poly_mul(&to_digits(x, p, k), &to_digits(y, p, k), p),
+                        &modulus,
+                        p,
+                    )[..k],
+                    p,
+                );
+            }
+        }
+
+        Some(Self { order: q, add, mul })
+    }
+
+    /// Sum of two field elements.
+    pub(crate) fn add(&self, x: usize, y: usize) -> usize {
+        self.add[x * self.order + y]
+    }
+
+    /// Product of two field elements.
+    pub(crate) fn mul(&self, x: usize, y: usize) -> usize {
+        self.mul[x * self.order + y]
+    }
+}
+
+/// Decomposes `q` as `p^k` for a prime `p`.
+pub(crate) fn prime_power(q: usize) -> Option<(usize, usize)> {
+    if q < 2 {
+        return None;
+    }
+    let p = (2..=q).find(|&d| q.is_multiple_of(d))?;
+    let mut rest = q;
+    let mut k = 0;
+    while rest.is_multiple_of(p) {
+        rest /= p;
+        k += 1;
+    }
+    (rest == 1).then_some((p, k))
+}
+
+fn to_digits(mut x: usize, p: usize, k: usize) -> Vec<usize> {
+    let mut digits = vec![0; k];
+    for d in &mut digits {
+        *d = x % p;
+        x /= p;
+    }
+    digits
+}
+
+fn from_digits(digits: &[usize], p: usize) -> usize {
+    digits.iter().rev().fold(0, |acc, &d| acc * p + d)
+}
+
+fn poly_mul(a: &[usize], b: &[usize], p: usize) -> Vec<usize> {
+    let mut out = vec![0; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            out[i + j] = (out[i + j] + x * y) % p;
+        }
+    }
+    out
+}
+
+/// Reduces `a` modulo the monic polynomial `m`, returning a vector at least as long as `m`.
+fn poly_mod(a: &[usize], m: &[usize], p: usize) -> Vec<usize> {
+    let deg = m.len() - 1;
+    let mut r = a.to_vec();
+    r.resize(r.len().max(m.len()), 0);
+    for i in (deg..r.len()).rev() {
+        let c = r[i];
+        if c != 0 {
+            for (j, &mj) in m.iter().enumerate() {
+                r[i - deg + j] = (r[i - deg + j] + (p - c) * mj) % p;
+            }
+        }
+    }
+    r
+}
+
+/// Finds the first monic irreducible polynomial of degree `k` over GF(p).
+fn irreducible(p: usize, k: usize) -> Vec<usize> {
+    let monic = |low: usize, deg: usize| {
+        let mut c = to_digits(low, p, deg);
+        c.push(1);
+        c
+    };
+
+    (0..p.pow(k as u32))
+        .map(|low| monic(low, k))
+        .find(|m| {
+            (1..=k / 2).all(|deg| {
+                (0..p.pow(deg as u32))
+                    .map(|low| monic(low, deg))
+                    .all(|d| poly_mod(m, &d, p)[..deg].iter().any(|&c| c != 0))
+            })
+        })
+        .unwrap_or_else(|| monic(0, k))
+}
+
+#[cfg(test)]
+mod test_field {
+    use super::*;
+
+    #[test]
+    fn test_prime_power() {
+        assert_eq!(prime_power(9), Some((3, 2)));
+        assert_eq!(prime_power(16), Some((2, 4)));
+        assert_eq!(prime_power(7), Some((7, 1)));
+        assert_eq!(prime_power(12), None);
+        assert_eq!(prime_power(1), None);
+    }
+
+    #[test]
+    fn test_field_axioms() {
+        for q in [2, 3, 4, 5, 8, 9, 16, 25, 27] {
+            let f = GaloisField::new(q).unwrap();
+            for x in 1..q {
+                assert_eq!((1..q).filter(|&y| f.mul(x, y) == 1).count(), 1);
+                assert_eq!((0..q).filter(|&y| f.add(x, y) == 0).count(), 1);
+                for y in 0..q {
+                    assert_eq!(f.mul(x, y), f.mul(y, x));
+                    assert_eq!(f.add(x, y), f.add(y, x));
+                }
+            }
+        }
+    }
+}
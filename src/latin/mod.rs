@@ -0,0 +1,8 @@
+mod euler;
+mod field;
+mod orthogonal;
+mod square;
+
+pub use euler::*;
+pub use orthogonal::*;
+pub use square::*;
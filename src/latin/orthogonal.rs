@@ -0,0 +1,125 @@
+use crate::latin::field::GaloisField;
+use crate::latin::square::is_orthogonal_pair;
+use crate::{gcd, LatinSquare, ParameterSetError, Params, VecSquare};
+
+impl<P: Params + Copy> LatinSquare<P> {
+    /// Generates mutually orthogonal Latin squares using the cyclic method.
+    ///
+    /// The squares are `L_k[i][j] = (k * i + j) mod n` for each `k` in `1..p`, where `p` is the
+    /// smallest prime factor of the order. Every such `k`, and every difference between two of
+    /// them, is a unit modulo `n`, so the `p - 1` squares are pairwise orthogonal. For even orders
+    /// this yields a single square.
+    #[must_use]
+    pub fn cyclic_mols() -> Vec<Self> {
+        let p = (2..=P::ORDER)
+            .find(|&d| P::ORDER.is_multiple_of(d))
+            .unwrap_or(P::ORDER);
+
+        (1..p.max(2))
+            .filter(|&k| gcd(k, P::ORDER) == 1)
+            .map(Self::cyclic)
+            .collect()
+    }
+
+    /// Generates a complete set of `n - 1` mutually orthogonal Latin squares of prime power
+    /// order `n` using the finite field GF(n).
+    ///
+    /// The squares are `L_a[x][y] = a * x + y` for each non-zero field element `a`, where rows and
+    /// columns are labelled by field elements. The result is ordered by the label of `a`.
+    ///
+    /// # Errors
+    /// - If the order of the square is not a prime power.
+    pub fn field_mols() -> Result<Vec<Self>, ParameterSetError> {
+        let field = GaloisField::new(P::ORDER).ok_or_else(|| {
+            ParameterSetError::InvalidOrder("Order is not a prime power!".to_string())
+        })?;
+
+        Ok((1..P::ORDER)
+            .map(|a| {
+                let data = (0..P::ELEMENTS)
+                    .map(|e| field.add(field.mul(a, e / P::ORDER), e % P::ORDER) as u32)
+                    .collect();
+                Self {
+                    square: VecSquare::from_vec(data),
+                }
+            })
+            .collect())
+    }
+
+    /// Generates mutually orthogonal Latin squares using the best method available for the
+    /// order: the finite field method for prime powers, otherwise the cyclic method.
+    #[must_use]
+    pub fn mols() -> Vec<Self> {
+        Self::field_mols().unwrap_or_else(|_| Self::cyclic_mols())
+    }
+
+    /// Finds a pair of orthogonal Latin squares that are both diagonal, which is exactly what
+    /// Euler's construction needs to produce a magic square.
+    ///
+    /// Returns `None` if no such pair exists among the squares produced by `mols`. For prime power
+    /// orders the field squares with `a` other than `0` and `±1` are diagonal, so a pair is found
+    /// for every prime power order above three.
+    #[must_use]
+    pub fn diagonal_pair() -> Option<(Self, Self)> {
+        let diagonal = Self::mols()
+            .into_iter()
+            .filter(LatinSquare::is_diagonal)
+            .collect::<Vec<_>>();
+
+        diagonal.iter().enumerate().find_map(|(i, a)| {
+            diagonal[i + 1..]
+                .iter()
+                .find(|b| is_orthogonal_pair::<P>(&a.square.data, &b.square.data))
+                .map(|b| (a.clone(), b.clone()))
+        })
+    }
+}
+
+/// Checks whether every pair in a collection of Latin squares is orthogonal.
+#[must_use]
+pub fn are_mutually_orthogonal<P: Params>(squares: &[LatinSquare<P>]) -> bool {
+    squares
+        .iter()
+        .enumerate()
+        .all(|(i, a)| squares[i + 1..].iter().all(|b| a.is_orthogonal(b)))
+}
+
+#[cfg(test)]
+mod test_orthogonal {
+    use crate::{O25, O3, O4, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_cyclic_mols() {
+        assert_eq!(LatinSquare::<O3>::cyclic_mols().len(), 2);
+        assert_eq!(LatinSquare::<O4>::cyclic_mols().len(), 1);
+        assert_eq!(LatinSquare::<O5>::cyclic_mols().len(), 4);
+        assert!(are_mutually_orthogonal(&LatinSquare::<O5>::cyclic_mols()));
+    }
+
+    #[test]
+    fn test_field_mols() -> Result<(), ParameterSetError> {
+        let four = LatinSquare::<O4>::field_mols()?;
+        let twenty_five = LatinSquare::<O25>::field_mols()?;
+
+        assert_eq!(four.len(), 3);
+        assert_eq!(twenty_five.len(), 24);
+        assert!(four.iter().all(|l| l.square.is_latin()));
+        assert!(are_mutually_orthogonal(&four));
+        assert!(are_mutually_orthogonal(&twenty_five));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagonal_pair() {
+        assert!(LatinSquare::<O3>::diagonal_pair().is_none());
+
+        let (a, b) = LatinSquare::<O4>::diagonal_pair().unwrap();
+        assert!(a.is_diagonal() && b.is_diagonal() && a.is_orthogonal(&b));
+
+        let (a, b) = LatinSquare::<O5>::diagonal_pair().unwrap();
+        assert!(a.is_diagonal() && b.is_diagonal() && a.is_orthogonal(&b));
+    }
+}
@@ -0,0 +1,193 @@
+use core::fmt;
+
+use crate::{gcd, ParameterSetError, Params, VecSquare};
+
+/// A generic struct representing a Latin square based on parameters `P` implementing the `Params`
+/// trait. Every row and every column contains each of the symbols `0..P::ORDER` exactly once.
+///
+/// # Examples
+///
+/// ```
+/// use lo_shu::{LatinSquare, O5};
+///
+/// let a = LatinSquare::<O5>::cyclic(2);
+/// let b = LatinSquare::<O5>::cyclic(3);
+///
+/// assert!(a.is_orthogonal(&b));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatinSquare<P: Params> {
+    pub square: VecSquare<P>,
+}
+
+impl<P: Params> LatinSquare<P> {
+    /// Creates the cyclic Latin square `L[i][j] = (k * i + j) mod n`.
+    ///
+    /// # Panics
+    /// - If `k` is not coprime to the order of the square.
+    #[must_use]
+    pub fn cyclic(k: usize) -> Self {
+        assert!(
+            gcd(k % P::ORDER, P::ORDER) == 1,
+            "Multiplier Must Be Coprime To The Order!"
+        );
+
+        let data = (0..P::ELEMENTS)
+            .map(|e| ((k * (e / P::ORDER) + e % P::ORDER) % P::ORDER) as u32)
+            .collect();
+        Self {
+            square: VecSquare::from_vec(data),
+        }
+    }
+
+    /// Returns the symbol at row `r` and column `c`.
+    #[must_use]
+    pub fn get(&self, r: usize, c: usize) -> u32 {
+        self.square.data[r * P::ORDER + c]
+    }
+
+    /// Returns the transpose of the Latin square, which is also Latin.
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let data = (0..P::ELEMENTS)
+            .map(|e| self.get(e % P::ORDER, e / P::ORDER))
+            .collect();
+        Self {
+            square: VecSquare::from_vec(data),
+        }
+    }
+
+    /// Checks whether both the main diagonal and the anti-diagonal are transversals, i.e. each
+    /// contains every symbol exactly once.
+    #[must_use]
+    pub fn is_diagonal(&self) -> bool {
+        is_transversal::<P>((0..P::ORDER).map(|i| self.get(i, i)))
+            && is_transversal::<P>((0..P::ORDER).map(|i| self.get(i, P::ORDER - 1 - i)))
+    }
+
+    /// Checks whether two Latin squares are orthogonal: superimposing them yields every ordered
+    /// pair of symbols exactly once.
+    #[must_use]
+    pub fn is_orthogonal(&self, other: &Self) -> bool {
+        is_orthogonal_pair::<P>(&self.square.data, &other.square.data)
+    }
+}
+
+impl<P: Params> VecSquare<P> {
+    /// Checks whether the square is Latin over the symbols `0..P::ORDER`.
+    #[must_use]
+    pub fn is_latin(&self) -> bool {
+        self.data.len() == P::ELEMENTS
+            && (0..P::ORDER).all(|i| {
+                is_transversal::<P>((0..P::ORDER).map(|j| self.data[i * P::ORDER + j]))
+                    && is_transversal::<P>((0..P::ORDER).map(|j| self.data[j * P::ORDER + i]))
+            })
+    }
+}
+
+impl<P: Params> TryFrom<&[u32]> for LatinSquare<P> {
+    type Error = ParameterSetError;
+
+    fn try_from(item: &[u32]) -> Result<Self, Self::Error> {
+        let square = VecSquare::<P>::try_from(item)?;
+
+        if square.is_latin() {
+            Ok(Self { square })
+        } else {
+            Err(ParameterSetError::InvalidElements(
+                "Item is not a Latin square over 0..P::ORDER!".to_string(),
+            ))
+        }
+    }
+}
+
+impl<P: Params> TryFrom<VecSquare<P>> for LatinSquare<P> {
+    type Error = ParameterSetError;
+
+    fn try_from(item: VecSquare<P>) -> Result<Self, Self::Error> {
+        Self::try_from(item.data.as_slice())
+    }
+}
+
+impl<P: Params> fmt::Display for LatinSquare<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.square)
+    }
+}
+
+/// Checks that an iterator of `P::ORDER` symbols contains each of `0..P::ORDER` exactly once.
+fn is_transversal<P: Params>(symbols: impl Iterator<Item = u32>) -> bool {
+    let mut seen = vec![false; P::ORDER];
+    let mut count = 0;
+    for s in symbols {
+        match seen.get_mut(s as usize) {
+            Some(slot) if !*slot => *slot = true,
+            _ => return false,
+        }
+        count += 1;
+    }
+    count == P::ORDER
+}
+
+/// Checks that superimposing `a` and `b` yields every ordered pair of symbols exactly once.
+pub(crate) fn is_orthogonal_pair<P: Params>(a: &[u32], b: &[u32]) -> bool {
+    let mut seen = vec![false; P::ELEMENTS];
+    a.len() == P::ELEMENTS
+        && b.len() == P::ELEMENTS
+        && a.iter().zip(b).all(|(&x, &y)| {
+            let (x, y) = (x as usize, y as usize);
+            if x >= P::ORDER || y >= P::ORDER || seen[x * P::ORDER + y] {
+                return false;
+            }
+            seen[x * P::ORDER + y] = true;
+            true
+        })
+}
+
+#[cfg(test)]
+mod test_latin_square {
+    use crate::{O3, O4, O5};
+
+    use super::*;
+
+    #[test]
+    fn test_cyclic_3() {
+        let a = LatinSquare::<O3>::cyclic(1);
+        let b = LatinSquare {
+            square: VecSquare::from_vec(vec![0, 1, 2, 1, 2, 0, 2, 0, 1]),
+        };
+
+        assert_eq!(a, b);
+        assert!(a.square.is_latin());
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert!(LatinSquare::<O3>::try_from([0, 1, 2, 2, 0, 1, 1, 2, 0].as_slice()).is_ok());
+        assert_eq!(
+            LatinSquare::<O3>::try_from([0, 1, 2, 0, 1, 2, 1, 2, 0].as_slice()),
+            Err(ParameterSetError::InvalidElements(
+                "Item is not a Latin square over 0..P::ORDER!".to_string()
+            ))
+        );
+        assert!(LatinSquare::<O3>::try_from([0, 1, 2].as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_orthogonal() {
+        let a = LatinSquare::<O5>::cyclic(1);
+        let b = LatinSquare::<O5>::cyclic(2);
+
+        assert!(a.is_orthogonal(&b));
+        assert!(!a.is_orthogonal(&a));
+        assert!(!a.transpose().is_orthogonal(&a));
+        assert!(b.transpose().is_orthogonal(&b));
+    }
+
+    #[test]
+    fn test_diagonal() {
+        assert!(LatinSquare::<O5>::cyclic(2).is_diagonal());
+        assert!(!LatinSquare::<O5>::cyclic(1).is_diagonal());
+        assert!(!LatinSquare::<O4>::cyclic(1).is_diagonal());
+    }
+}
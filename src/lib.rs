@@ -7,16 +7,20 @@
 #![allow(clippy::items_after_statements)]
 #![allow(clippy::many_single_char_names)]
 
+pub mod analysis;
 pub mod checkers;
 pub mod constructive;
 pub mod core;
 pub mod group;
+pub mod latin;
 pub mod order;
 pub mod prelude;
 
+pub use crate::analysis::*;
 pub use crate::checkers::*;
 pub use crate::constructive::*;
 pub use crate::core::*;
 pub use crate::group::*;
+pub use crate::latin::*;
 pub use crate::order::*;
 pub use crate::prelude::*;
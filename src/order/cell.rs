@@ -0,0 +1,61 @@
+use std::fmt;
+use std::hash::Hash;
+
+/// An unsigned integer type holding the entries of a compact square.
+///
+/// Every cell converts losslessly to a `u32`, and a `u32` converts back whenever it is at most
+/// `Cell::MAX`.
+pub trait Cell:
+    Copy
+    + Default
+    + Ord
+    + Hash
+    + fmt::Debug
+    + fmt::Display
+    + Send
+    + Sync
+    + Into<u32>
+    + TryFrom<u32>
+    + 'static
+{
+    /// The largest entry a cell holds.
+    const MAX: u32;
+
+    /// Returns `value` as a cell, or `None` if it is greater than `Cell::MAX`.
+    #[must_use]
+    fn from_u32(value: u32) -> Option<Self> {
+        Self::try_from(value).ok()
+    }
+
+    /// Returns the cell as a `u32`.
+    #[must_use]
+    fn to_u32(self) -> u32 {
+        self.into()
+    }
+}
+
+impl Cell for u8 {
+    const MAX: u32 = u8::MAX as u32;
+}
+
+impl Cell for u16 {
+    const MAX: u32 = u16::MAX as u32;
+}
+
+impl Cell for u32 {
+    const MAX: u32 = u32::MAX;
+}
+
+#[cfg(test)]
+mod test_cell {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(u8::from_u32(255).map(Cell::to_u32), Some(255));
+        assert_eq!(u8::from_u32(256), None);
+        assert_eq!(u16::from_u32(65_535).map(Cell::to_u32), Some(65_535));
+        assert_eq!(u16::from_u32(65_536), None);
+        assert_eq!(u32::from_u32(u32::MAX), Some(u32::MAX));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod cell;
 pub mod params;
 
+pub use cell::*;
 pub use params::*;
@@ -1,14 +1,19 @@
+use crate::order::Cell;
+
 /// A trait for parameter sets defining the properties of a square.
 pub trait Params {
     const ORDER: usize;
     const ELEMENTS: usize;
     const MAGIC_SUM: u32;
     const CONSTRAINT_VECTORS: usize;
+    /// The smallest cell holding every entry `1..=ELEMENTS` of a normal square: `u8` up to order
+    /// 15, `u16` up to order 255 and `u32` beyond.
+    type Cell: Cell;
 }
 
 /// Macro to generate implementations of the Params trait for different parameter sets.
 macro_rules! impl_parameter_set {
-    ($order:literal, $name:tt) => {
+    ($order:literal, $name:tt, $cell:ty) => {
         #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
         pub struct $name;
 
@@ -18,14 +23,95 @@ macro_rules! impl_parameter_set {
             const MAGIC_SUM: u32 =
                 (((Self::ELEMENTS * (Self::ELEMENTS + 1)) / 2) / Self::ORDER) as u32;
             const CONSTRAINT_VECTORS: usize = Self::ORDER * 2 + 2;
+            type Cell = $cell;
         }
     };
 }
 
 // Generate implementations for specific parameter sets.
-impl_parameter_set!(3, O3);
-impl_parameter_set!(4, O4);
-impl_parameter_set!(5, O5);
-impl_parameter_set!(25, O25);
-impl_parameter_set!(301, O301);
-impl_parameter_set!(301, O1001);
+impl_parameter_set!(3, O3, u8);
+impl_parameter_set!(4, O4, u8);
+impl_parameter_set!(5, O5, u8);
+impl_parameter_set!(6, O6, u8);
+impl_parameter_set!(7, O7, u8);
+impl_parameter_set!(8, O8, u8);
+impl_parameter_set!(9, O9, u8);
+impl_parameter_set!(10, O10, u8);
+impl_parameter_set!(11, O11, u8);
+impl_parameter_set!(12, O12, u8);
+impl_parameter_set!(13, O13, u8);
+impl_parameter_set!(14, O14, u8);
+impl_parameter_set!(15, O15, u8);
+impl_parameter_set!(16, O16, u16);
+impl_parameter_set!(20, O20, u16);
+impl_parameter_set!(25, O25, u16);
+impl_parameter_set!(30, O30, u16);
+impl_parameter_set!(301, O301, u32);
+impl_parameter_set!(301, O1001, u32);
+
+/// A trait extending a parameter set to a `D`-dimensional hypercube of the same order.
+///
+/// Implemented for every parameter set and dimension, so that `O5` with `D = 3` describes the
+/// order 5 magic cube. The names differ from those of `Params`, which only describe the square.
+pub trait HyperParams<const D: usize>: Params {
+    /// The number of cells, `ORDER ^ D`.
+    const CELLS: usize;
+    /// The sum of every line of a normal magic hypercube.
+    const LINE_SUM: u64;
+    /// The number of lines of a simple magic hypercube: every axis-parallel line and the
+    /// `2 ^ (D - 1)` main diagonals.
+    const LINES: usize;
+}
+
+impl<P: Params, const D: usize> HyperParams<D> for P {
+    const CELLS: usize = P::ORDER.pow(D as u32);
+    const LINE_SUM: u64 = (P::ORDER * (<Self as HyperParams<D>>::CELLS + 1) / 2) as u64;
+    const LINES: usize = D * P::ORDER.pow(D as u32 - 1) + (1 << (D - 1));
+}
+
+/// A trait for parameter sets defining a rectangle of `ROWS` rows and `COLS` columns, whose rows
+/// and columns have different lengths and so different sums.
+///
+/// Implemented for every square parameter set with `ROWS = COLS = ORDER`, so that a square is the
+/// rectangle with equal sides. The names differ from those of `Params` for the same reason as
+/// `HyperParams`.
+pub trait RectParams {
+    const ROWS: usize;
+    const COLS: usize;
+    /// The sum of every row of a normal magic rectangle.
+    const ROW_SUM: u32;
+    /// The sum of every column of a normal magic rectangle.
+    const COL_SUM: u32;
+}
+
+impl<P: Params> RectParams for P {
+    const ROWS: usize = P::ORDER;
+    const COLS: usize = P::ORDER;
+    const ROW_SUM: u32 = P::MAGIC_SUM;
+    const COL_SUM: u32 = P::MAGIC_SUM;
+}
+
+/// Macro to generate implementations of the `RectParams` trait for different parameter sets.
+macro_rules! impl_rectangle_set {
+    ($rows:literal, $cols:literal, $name:tt) => {
+        #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+        pub struct $name;
+
+        impl RectParams for $name {
+            const ROWS: usize = $rows;
+            const COLS: usize = $cols;
+            const ROW_SUM: u32 = ((Self::COLS * (Self::ROWS * Self::COLS + 1)) / 2) as u32;
+            const COL_SUM: u32 = ((Self::ROWS * (Self::ROWS * Self::COLS + 1)) / 2) as u32;
+        }
+    };
+}
+
+// Generate implementations for specific rectangle parameter sets.
+impl_rectangle_set!(2, 4, R2x4);
+impl_rectangle_set!(2, 6, R2x6);
+impl_rectangle_set!(3, 5, R3x5);
+impl_rectangle_set!(3, 9, R3x9);
+impl_rectangle_set!(4, 6, R4x6);
+impl_rectangle_set!(5, 7, R5x7);
+impl_rectangle_set!(6, 10, R6x10);
+impl_rectangle_set!(7, 9, R7x9);
@@ -0,0 +1,430 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::hash::{BuildHasherDefault, Hasher};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::{PackedPerm16, Permutation, O4};
+
+/// Counts the runs of `ShardedFactors` in this process, to name their run files apart.
+static RUNS: AtomicU64 = AtomicU64::new(0);
+
+/// Statistics of a run of `ShardedFactors`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FactorStats {
+    /// The number of products `a.inv() * c` computed.
+    pub products: u64,
+    /// The number of distinct factors among the products.
+    pub factors: usize,
+    /// The most bytes held at once by the shard sets, the product buffers and the result,
+    /// estimated from their capacities.
+    pub peak_bytes: usize,
+    /// The number of run files written while spilling.
+    pub spilled_runs: usize,
+    /// The number of bytes written to run files.
+    pub spilled_bytes: u64,
+    /// The wall-clock time of the run.
+    pub elapsed: Duration,
+}
+
+impl FactorStats {
+    /// Returns the number of products computed per second.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn throughput(&self) -> f64 {
+        self.products as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+impl fmt::Display for FactorStats {
+    #[allow(clippy::cast_precision_loss)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MIB: f64 = 1024.0 * 1024.0;
+
+        write!(
+            f,
+            "{} products, {} factors in {:.2?} ({:.1} M products/s), peak {:.1} MiB",
+            self.products,
+            self.factors,
+            self.elapsed,
+            self.throughput() / 1e6,
+            self.peak_bytes as f64 / MIB
+        )?;
+        if self.spilled_runs > 0 {
+            write!(
+                f,
+                ", {} runs spilled ({:.1} MiB)",
+                self.spilled_runs,
+                self.spilled_bytes as f64 / MIB
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes the factors `b = a.inv() * c` of every pair of squares `a` and `c` of a set, without
+/// holding every product at once.
+///
+/// The products are packed as `PackedPerm16` and split by hash across `shards` sets, so that
+/// equal products meet, and are deduplicated, in the same shard. Each worker buffers its products
+/// per shard and merges a full buffer into the shard under its lock. With `spill_to`, a shard
+/// holding `limit` factors writes them to a run file and starts over; its runs are read back
+/// one shard at a time at the end. The shards are then concatenated and sorted.
+///
+/// The shards are hashed, not held per thread: every worker merges into the same shared shards,
+/// each behind a `Mutex`, so that a factor is only ever stored once. Buffering keeps the locks
+/// to one per `buffer` products.
+///
+/// # Examples
+///
+/// ```no_run
+/// use lo_shu::prelude::ShardedFactors;
+/// use lo_shu::{Enumerable, Permutation, O4};
+///
+/// let set = [80_867_885_530, 80_907_762_010]
+///     .map(Permutation::<O4>::kth)
+///     .into_iter()
+///     .collect();
+/// let (factors, stats) = ShardedFactors::new().shards(16).run(&set).unwrap();
+/// println!("{stats}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShardedFactors {
+    shards: usize,
+    buffer: usize,
+    spill: Option<(PathBuf, usize)>,
+}
+
+impl Default for ShardedFactors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShardedFactors {
+    /// Creates a pipeline with four shards per thread of the rayon pool, without spilling.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            shards: 4 * rayon::current_num_threads(),
+            buffer: 4096,
+            spill: None,
+        }
+    }
+
+    /// Sets the number of shards.
+    ///
+    /// # Panics
+    /// - If `shards` is 0.
+    #[must_use]
+    pub fn shards(mut self, shards: usize) -> Self {
+        assert!(shards > 0, "There must be at least one shard!");
+        self.shards = shards;
+        self
+    }
+
+    /// Sets the number of products a worker buffers for each shard before merging them.
+    ///
+    /// # Panics
+    /// - If `buffer` is 0.
+    #[must_use]
+    pub fn buffer(mut self, buffer: usize) -> Self {
+        assert!(buffer > 0, "Buffers must hold at least one product!");
+        self.buffer = buffer;
+        self
+    }
+
+    /// Spills a shard to a run file in `dir` whenever it holds `limit` factors. The directory is
+    /// created if needed, and the run files are removed once they are read back. Run files are
+    /// named after the process and the run, so several runs may share a directory.
+    ///
+    /// # Panics
+    /// - If `limit` is 0.
+    #[must_use]
+    pub fn spill_to<Q: AsRef<Path>>(mut self, dir: Q, limit: usize) -> Self {
+        assert!(
+            limit > 0,
+            "Shards must hold at least one factor before spilling!"
+        );
+        self.spill = Some((dir.as_ref().to_path_buf(), limit));
+        self
+    }
+
+    /// Computes the factors of every pair of squares of `set`, sorted, along with statistics of
+    /// the run.
+    ///
+    /// # Errors
+    /// - If a run file cannot be written, read or removed while spilling.
+    ///
+    /// # Panics
+    /// - If a square of `set` is not a permutation of `1..=16`.
+    pub fn run(
+        &self,
+        set: &BTreeSet<Permutation<O4>>,
+    ) -> io::Result<(Vec<PackedPerm16>, FactorStats)> {
+        let start = Instant::now();
+        if let Some((dir, _)) = &self.spill {
+            fs::create_dir_all(dir)?;
+        }
+        let prefix = format!("{}-{}", process::id(), RUNS.fetch_add(1, Ordering::Relaxed));
+
+        let packed = set
+            .iter()
+            .map(|&p| PackedPerm16::try_from(p).expect("Square must be a permutation of 1..=16!"))
+            .collect::<Vec<_>>();
+        let inverses = packed.iter().map(|a| a.inv()).collect::<Vec<_>>();
+
+        let shards = (0..self.shards)
+            .map(|_| Mutex::new(Shard::default()))
+            .collect::<Vec<_>>();
+        let meter = Meter::default();
+        let spilled = AtomicU64::new(0);
+
+        // A few chunks of rows per thread, so that idle threads can steal work.
+        let rows = inverses
+            .len()
+            .div_ceil(8 * rayon::current_num_threads())
+            .max(1);
+        inverses.par_chunks(rows).try_for_each(|chunk| {
+            let buffered = self.shards * self.buffer * size_of::<PackedPerm16>();
+            meter.resize(0, buffered);
+
+            let mut buffers = vec![Vec::with_capacity(self.buffer); self.shards];
+            for &a_inv in chunk {
+                for &c in &packed {
+                    let factor = a_inv * c;
+                    let s = self.shard_of(factor);
+                    buffers[s].push(factor);
+                    if buffers[s].len() == self.buffer {
+                        self.merge(&shards[s], s, &prefix, &mut buffers[s], &meter, &spilled)?;
+                    }
+                }
+            }
+            for (s, buffer) in buffers.iter_mut().enumerate() {
+                self.merge(&shards[s], s, &prefix, buffer, &meter, &spilled)?;
+            }
+
+            meter.resize(buffered, 0);
+            Ok::<_, io::Error>(())
+        })?;
+
+        let mut factors = Vec::new();
+        let mut spilled_runs = 0;
+        for shard in shards {
+            let Shard { mut set, runs, .. } = shard.into_inner().unwrap();
+            spilled_runs += runs.len();
+
+            let before = Shard::bytes(&set);
+            for run in runs {
+                set.extend(fs::read(&run)?.chunks_exact(size_of::<u64>()).map(|b| {
+                    PackedPerm16::from_u64_unchecked(u64::from_le_bytes(b.try_into().unwrap()))
+                }));
+                fs::remove_file(run)?;
+            }
+            meter.resize(before, Shard::bytes(&set));
+
+            let capacity = factors.capacity() * size_of::<PackedPerm16>();
+            factors.extend(set.iter().copied());
+            meter.resize(capacity, factors.capacity() * size_of::<PackedPerm16>());
+            meter.resize(Shard::bytes(&set), 0);
+        }
+        factors.par_sort_unstable();
+
+        let stats = FactorStats {
+            products: packed.len() as u64 * packed.len() as u64,
+            factors: factors.len(),
+            peak_bytes: meter.peak.load(Ordering::Relaxed),
+            spilled_runs,
+            spilled_bytes: spilled.load(Ordering::Relaxed),
+            elapsed: start.elapsed(),
+        };
+
+        Ok((factors, stats))
+    }
+
+    /// Chooses the shard of a factor by its multiplicative hash.
+    #[inline]
+    fn shard_of(&self, factor: PackedPerm16) -> usize {
+        let hash = factor.as_u64().wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32;
+        hash as usize % self.shards
+    }
+
+    /// Merges a buffer of products into its shard, spilling the shard if it is full. Run files
+    /// are named after the `prefix` of the run.
+    fn merge(
+        &self,
+        shard: &Mutex<Shard>,
+        index: usize,
+        prefix: &str,
+        buffer: &mut Vec<PackedPerm16>,
+        meter: &Meter,
+        spilled: &AtomicU64,
+    ) -> io::Result<()> {
+        let mut shard = shard.lock().unwrap();
+        let before = Shard::bytes(&shard.set);
+        shard.set.extend(buffer.drain(..));
+
+        if let Some((dir, limit)) = &self.spill {
+            if shard.set.len() >= *limit {
+                let path = dir.join(format!(
+                    "{prefix}-shard-{index}-run-{}.bin",
+                    shard.runs.len()
+                ));
+                let mut file = BufWriter::new(File::create(&path)?);
+                for factor in &shard.set {
+                    file.write_all(&factor.as_u64().to_le_bytes())?;
+                }
+                file.flush()?;
+
+                spilled.fetch_add(
+                    (shard.set.len() * size_of::<u64>()) as u64,
+                    Ordering::Relaxed,
+                );
+                shard.runs.push(path);
+                shard.set = FactorSet::default();
+            }
+        }
+
+        meter.resize(before, Shard::bytes(&shard.set));
+        Ok(())
+    }
+}
+
+/// Hashes a packed factor with a folded multiply, which spreads it over both the high and the
+/// low bits far faster than the default hasher.
+#[derive(Default)]
+struct FactorHasher(u64);
+
+impl Hasher for FactorHasher {
+    fn finish(&self) -> u64 {
+        let product = u128::from(self.0) * 0xA076_1D64_78BD_642F;
+        (product >> 64) as u64 ^ product as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(b);
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 ^= i;
+    }
+}
+
+type FactorSet = HashSet<PackedPerm16, BuildHasherDefault<FactorHasher>>;
+
+/// The factors of one shard held in memory, and the run files it has spilled.
+#[derive(Default)]
+struct Shard {
+    set: FactorSet,
+    runs: Vec<PathBuf>,
+}
+
+impl Shard {
+    /// Estimates the bytes held by a set: a slot and a control byte for every bucket, of which
+    /// at most seven in eight are filled.
+    fn bytes(set: &FactorSet) -> usize {
+        set.capacity() * 8 / 7 * (size_of::<PackedPerm16>() + 1)
+    }
+}
+
+/// Tracks the bytes held at once, and the most ever held.
+#[derive(Default)]
+struct Meter {
+    resident: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl Meter {
+    /// Records that an allocation of `old` bytes now holds `new` bytes.
+    fn resize(&self, old: usize, new: usize) {
+        let resident = if new >= old {
+            self.resident.fetch_add(new - old, Ordering::Relaxed) + new - old
+        } else {
+            self.resident.fetch_sub(old - new, Ordering::Relaxed) - (old - new)
+        };
+        self.peak.fetch_max(resident, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test_factors {
+    use itertools::Itertools;
+
+    use crate::Enumerable;
+
+    use super::*;
+
+    fn squares() -> BTreeSet<Permutation<O4>> {
+        let magic = Permutation::<O4>::kth(80_867_885_530);
+        let actions = [1, 400, 5_000_000_000, 20_922_789_887_999].map(Permutation::<O4>::kth);
+        magic
+            .generate_d_array()
+            .into_iter()
+            .cartesian_product(actions)
+            .map(|(a, b)| a * b)
+            .collect()
+    }
+
+    fn expected(set: &BTreeSet<Permutation<O4>>) -> Vec<Permutation<O4>> {
+        set.iter()
+            .cartesian_product(set.iter())
+            .map(|(&a, &c)| a.inv() * c)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_in_memory() -> io::Result<()> {
+        let set = squares();
+        let (factors, stats) = ShardedFactors::new().shards(7).buffer(5).run(&set)?;
+
+        assert!(factors
+            .iter()
+            .map(|&f| Permutation::from(f))
+            .eq(expected(&set)));
+        assert_eq!(stats.products, (set.len() * set.len()) as u64);
+        assert_eq!(stats.factors, factors.len());
+        assert_eq!(stats.spilled_runs, 0);
+        assert!(stats.peak_bytes >= factors.len() * size_of::<PackedPerm16>());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spill() -> io::Result<()> {
+        let set = squares();
+        let dir = std::env::temp_dir().join(format!("lo-shu-factors-{}", std::process::id()));
+        let pipeline = ShardedFactors::new()
+            .shards(3)
+            .buffer(16)
+            .spill_to(&dir, 10);
+
+        // Two runs spilling to the same directory at once keep their runs apart.
+        let (a, b) = std::thread::scope(|s| {
+            let a = s.spawn(|| pipeline.run(&set));
+            let b = s.spawn(|| pipeline.run(&set));
+            (a.join().unwrap(), b.join().unwrap())
+        });
+        for (factors, stats) in [a?, b?] {
+            assert!(factors
+                .iter()
+                .map(|&f| Permutation::from(f))
+                .eq(expected(&set)));
+            assert!(stats.spilled_runs > 0);
+            assert!(stats.spilled_bytes >= 10 * 8);
+        }
+        assert_eq!(fs::read_dir(&dir)?.count(), 0);
+        fs::remove_dir(&dir)
+    }
+}
@@ -1,3 +1,5 @@
+pub mod factors;
 pub mod tools;
 
+pub use factors::*;
 pub use tools::*;
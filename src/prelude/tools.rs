@@ -1,9 +1,20 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 
 use itertools::Itertools;
-use rayon::iter::{ParallelBridge, ParallelIterator};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use crate::{Enumerable, Permutation, O4};
+use crate::prelude::ShardedFactors;
+use crate::{magic_batch_v, Enumerable, PackedPerm16, Permutation, O4};
+
+/// Packs every permutation of a set, in order.
+///
+/// # Panics
+/// - If a square of `set` is not a permutation of `1..=16`.
+fn pack_set(set: &BTreeSet<Permutation<O4>>) -> Vec<PackedPerm16> {
+    set.iter()
+        .map(|&p| PackedPerm16::try_from(p).expect("Square must be a permutation of 1..=16!"))
+        .collect()
+}
 
 /// Converts a k-set into a set of permutations.
 ///
@@ -25,7 +36,7 @@ pub fn k_set_to_permutation_set(set: &BTreeSet<u64>, take: usize) -> BTreeSet<Pe
 /// collects them into a `BTreeSet` before returning it.
 #[must_use]
 pub fn generate_permutation_isometries(perm: &Permutation<O4>) -> BTreeSet<Permutation<O4>> {
-    perm.generate_d().into_iter().collect()
+    perm.generate_d_array().into_iter().collect()
 }
 
 /// Minimizes the isometry of a permutation.
@@ -35,9 +46,10 @@ pub fn generate_permutation_isometries(perm: &Permutation<O4>) -> BTreeSet<Permu
 /// found, for whatever reason, returns the identity permutation.
 #[must_use]
 pub fn minimize_permutation_isometry(perm: &Permutation<O4>) -> Permutation<O4> {
-    *generate_permutation_isometries(perm)
-        .first()
-        .unwrap_or(&Permutation::identity())
+    perm.generate_d_array()
+        .into_iter()
+        .min()
+        .unwrap_or_else(Permutation::identity)
 }
 
 /// Minimizes the isometries of a set of permutations.
@@ -55,35 +67,43 @@ pub fn minimize_set_isometry(set: &BTreeSet<Permutation<O4>>) -> BTreeSet<Permut
 ///
 /// Given a reference to a `BTreeSet<Permutation<O4>>` `origin`, iterates over its elements,
 /// removing any isometry that is a combination of other isometries already in the set. Returns a
-/// new `BTreeSet` containing the reduced set of isometries.
+/// new `BTreeSet` containing the reduced set of isometries. The isometries are generated and
+/// looked up in packed form, as `PackedPerm16`.
+///
+/// # Panics
+/// - If a square of `origin` is not a permutation of `1..=16`.
 #[must_use]
 pub fn reduce_isometry(origin: &BTreeSet<Permutation<O4>>) -> BTreeSet<Permutation<O4>> {
-    let mut unique_set = BTreeSet::new();
-    for s in origin {
-        if unique_set
-            .intersection(&s.generate_d().into_iter().collect())
-            .copied()
-            .collect::<BTreeSet<_>>()
-            .is_empty()
-        {
-            unique_set.insert(*s);
-        }
-    }
-    unique_set
+    let mut seen = HashSet::new();
+    pack_set(origin)
+        .into_iter()
+        .filter(|s| {
+            let unique = !s.generate_d().iter().any(|e| seen.contains(e));
+            if unique {
+                seen.insert(*s);
+            }
+            unique
+        })
+        .map(Permutation::from)
+        .collect()
 }
 
 /// Computes the transformations between squares in a set.
 ///
 /// Given a reference to a `BTreeSet<Permutation<O4>>` `set`, computes the transformation `b` from
 /// the factored action `a * b = c` for each pair of permutations `a` and `c` in the set. Returns a
-///  ew `BTreeSet` containing the computed transformations.
+/// new `BTreeSet` containing the computed transformations. The products are deduplicated in
+/// memory by `ShardedFactors`; run it directly for the statistics of the computation.
+///
+/// # Panics
+/// - If a square of `set` is not a permutation of `1..=16`.
 #[must_use]
 pub fn compute_factors_ainvc(set: &BTreeSet<Permutation<O4>>) -> BTreeSet<Permutation<O4>> {
-    set.iter()
-        .cartesian_product(set.iter())
-        .par_bridge()
-        .map(|(&a, &c)| a.inv() * c)
-        .collect::<BTreeSet<_>>()
+    let (factors, _) = ShardedFactors::new()
+        .run(set)
+        .unwrap_or_else(|_| unreachable!("The in-memory pipeline performs no I/O!"));
+
+    factors.into_iter().map(Permutation::from).collect()
 }
 
 /// Extends a set of permutations with their associated isometries.
@@ -95,6 +115,103 @@ pub fn compute_factors_ainvc(set: &BTreeSet<Permutation<O4>>) -> BTreeSet<Permut
 pub fn extend_isometry(source: &BTreeSet<Permutation<O4>>) -> BTreeSet<Permutation<O4>> {
     source
         .iter()
-        .flat_map(|&item| item.generate_d().into_iter())
+        .flat_map(|&item| item.generate_d_array())
+        .collect()
+}
+
+/// Extends a set of squares by the transformations between them.
+///
+/// Given a reference to a `BTreeSet<Permutation<O4>>` `set` and a reference to a
+/// `BTreeSet<Permutation<O4>>` of transformations `actions`, computes `a * e.inv()` for each
+/// permutation `a` in the set and each isometry `e` of each transformation, and collects the magic
+/// ones into a new `BTreeSet` before returning it. The products of each permutation are checked
+/// together with `magic_batch_v`.
+#[must_use]
+pub fn extend_by_actions(
+    set: &BTreeSet<Permutation<O4>>,
+    actions: &BTreeSet<Permutation<O4>>,
+) -> BTreeSet<Permutation<O4>> {
+    let inverses = actions
+        .iter()
+        .flat_map(|b| b.generate_d_array().map(|e| e.inv()))
+        .sorted_unstable()
+        .dedup()
+        .collect_vec();
+
+    set.par_iter()
+        .flat_map_iter(|&a| {
+            let products = inverses.iter().map(|&e| a * e).collect_vec();
+            magic_batch_v(&products)
+        })
         .collect()
 }
+
+#[cfg(test)]
+mod test_tools {
+    use super::*;
+
+    fn census(take: usize) -> BTreeSet<Permutation<O4>> {
+        let magic = Permutation::<O4>::kth(80_867_885_530);
+        let actions = [1, 400, 5_000_000_000, 20_922_789_887_999].map(Permutation::<O4>::kth);
+        magic
+            .generate_d()
+            .into_iter()
+            .cartesian_product(actions)
+            .map(|(a, b)| a * b)
+            .take(take)
+            .collect()
+    }
+
+    #[test]
+    fn test_reduce_isometry() {
+        let set = census(32);
+        let mut expected = BTreeSet::new();
+        for s in &set {
+            if s.generate_d().iter().all(|e| !expected.contains(e)) {
+                expected.insert(*s);
+            }
+        }
+
+        assert_eq!(reduce_isometry(&set), expected);
+        assert_eq!(
+            reduce_isometry(&extend_isometry(&set)).len(),
+            expected.len()
+        );
+    }
+
+    #[test]
+    fn test_compute_factors_ainvc() {
+        let set = census(32);
+        let expected = set
+            .iter()
+            .cartesian_product(set.iter())
+            .map(|(&a, &c)| a.inv() * c)
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(compute_factors_ainvc(&set), expected);
+    }
+
+    #[test]
+    fn test_extend_by_actions() {
+        let set = Permutation::<O4>::kth(80_867_885_530)
+            .generate_d()
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let mut actions = compute_factors_ainvc(&set);
+        actions.extend([400, 5_000_000_000].map(Permutation::<O4>::kth));
+
+        let mut expected = BTreeSet::new();
+        for a in &set {
+            for b in &actions {
+                for e in b.generate_d() {
+                    if let Some(p) = (*a * e.inv()).check_n_s() {
+                        expected.insert(p);
+                    }
+                }
+            }
+        }
+
+        assert!(!expected.is_empty());
+        assert_eq!(extend_by_actions(&set, &actions), expected);
+    }
+}